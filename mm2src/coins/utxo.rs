@@ -693,6 +693,22 @@ pub fn compressed_pub_key_from_priv_raw(raw_priv: &[u8], sum_type: ChecksumType)
     Ok(H264::from(&**key_pair.public()))
 }
 
+/// Returns the checksum type set by the `checksum_type` field of the coin config
+/// or the default checksum type of the coin if the field is not set.
+pub fn checksum_type_from_conf(ticker: &str, conf: &Json) -> Result<ChecksumType, String> {
+    match conf["checksum_type"].as_str() {
+        Some("DSHA256") => Ok(ChecksumType::DSHA256),
+        Some("DGROESTL512") => Ok(ChecksumType::DGROESTL512),
+        Some("KECCAK256") => Ok(ChecksumType::KECCAK256),
+        Some(unknown) => ERR!("Unknown checksum_type {}", unknown),
+        None => match ticker {
+            "GRS" => Ok(ChecksumType::DGROESTL512),
+            "SMART" => Ok(ChecksumType::KECCAK256),
+            _ => Ok(ChecksumType::DSHA256),
+        },
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct UtxoFeeDetails {
     pub amount: BigDecimal,
@@ -829,7 +845,7 @@ impl<'a> UtxoConfBuilder<'a> {
     pub fn new(conf: &'a Json, req: &'a Json, ticker: &'a str) -> Self { UtxoConfBuilder { conf, req, ticker } }
 
     pub fn build(&self) -> Result<UtxoCoinConf, String> {
-        let checksum_type = self.checksum_type();
+        let checksum_type = try_s!(self.checksum_type());
         let pub_addr_prefix = self.pub_addr_prefix();
         let p2sh_addr_prefix = self.p2sh_address_prefix();
         let pub_t_addr_prefix = self.pub_t_address_prefix();
@@ -897,13 +913,7 @@ impl<'a> UtxoConfBuilder<'a> {
         })
     }
 
-    fn checksum_type(&self) -> ChecksumType {
-        match self.ticker {
-            "GRS" => ChecksumType::DGROESTL512,
-            "SMART" => ChecksumType::KECCAK256,
-            _ => ChecksumType::DSHA256,
-        }
-    }
+    fn checksum_type(&self) -> Result<ChecksumType, String> { checksum_type_from_conf(self.ticker, self.conf) }
 
     fn pub_addr_prefix(&self) -> u8 {
         let pubtype = self.conf["pubtype"]
@@ -2699,3 +2699,38 @@ fn test_parse_tx_with_huge_locktime() {
     let verbose_tx: RpcTransaction = json::from_str(verbose).expect("!json::from_str");
     let _: UtxoTx = deserialize(verbose_tx.hex.as_slice()).unwrap();
 }
+
+#[test]
+fn test_checksum_type_from_conf() {
+    let key_pair =
+        key_pair_from_seed("spice describe gravity federal blast come thank unfair canal monkey style afraid").unwrap();
+    let req = json!({"method": "electrum"});
+    let address_for_conf = |conf: &Json| -> Result<Address, String> {
+        let conf = try_s!(UtxoConfBuilder::new(conf, &req, "MYCOIN").build());
+        Ok(Address {
+            prefix: conf.pub_addr_prefix,
+            hash: key_pair.public().address_hash(),
+            t_addr_prefix: conf.pub_t_addr_prefix,
+            checksum_type: conf.checksum_type,
+        })
+    };
+
+    let default_address = address_for_conf(&json!({"coin": "MYCOIN", "pubtype": 60})).unwrap();
+    assert_eq!(default_address.checksum_type, ChecksumType::DSHA256);
+
+    let keccak_address =
+        address_for_conf(&json!({"coin": "MYCOIN", "pubtype": 60, "checksum_type": "KECCAK256"})).unwrap();
+    assert_eq!(keccak_address.checksum_type, ChecksumType::KECCAK256);
+    // the same hash gives a different address string as the checksum differs
+    assert_eq!(keccak_address.hash, default_address.hash);
+    assert_ne!(keccak_address.to_string(), default_address.to_string());
+
+    // the checksum type is detected on parse
+    let parsed: Address = keccak_address.to_string().parse().unwrap();
+    assert_eq!(parsed, keccak_address);
+
+    let error = address_for_conf(&json!({"coin": "MYCOIN", "pubtype": 60, "checksum_type": "SHA3"}))
+        .err()
+        .unwrap();
+    assert!(error.contains("Unknown checksum_type SHA3"));
+}
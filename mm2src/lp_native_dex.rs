@@ -31,8 +31,8 @@ use std::str;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::mm2::database::init_and_migrate_db;
 use crate::mm2::lp_network::{p2p_event_process_loop, P2PContext};
-use crate::mm2::lp_ordermatch::{broadcast_maker_orders_keep_alive_loop, lp_ordermatch_loop, orders_kick_start,
-                                BalanceUpdateOrdermatchHandler};
+use crate::mm2::lp_ordermatch::{broadcast_maker_orders_keep_alive_loop, lp_ordermatch_loop, matched_swaps_kick_start,
+                                orders_kick_start, validate_ordermatch_conf, BalanceUpdateOrdermatchHandler};
 use crate::mm2::lp_swap::{running_swaps_num, swap_kick_starts};
 use crate::mm2::rpc::spawn_rpc;
 use crate::mm2::{MM_DATETIME, MM_VERSION};
@@ -176,6 +176,9 @@ fn fix_directories(ctx: &MmCtx) -> Result<(), String> {
     if !ensure_dir_is_writable(&dbdir.join("ORDERS").join("MY").join("HISTORY")) {
         return ERR!("ORDERS/MY/HISTORY db dir is not writable");
     }
+    if !ensure_dir_is_writable(&dbdir.join("ORDERS").join("MY").join("MATCHED")) {
+        return ERR!("ORDERS/MY/MATCHED db dir is not writable");
+    }
     if !ensure_dir_is_writable(&dbdir.join("TX_CACHE")) {
         return ERR!("TX_CACHE db dir is not writable");
     }
@@ -208,6 +211,7 @@ fn fix_directories(ctx: &MmCtx) -> Result<(), String> {
     writeable_dir!(dbdir.join("SWAPS").join("STATS").join("TAKER"));
     writeable_dir!(dbdir.join("ORDERS").join("MY").join("MAKER"));
     writeable_dir!(dbdir.join("ORDERS").join("MY").join("TAKER"));
+    writeable_dir!(dbdir.join("ORDERS").join("MY").join("MATCHED"));
     Ok(())
 }
 
@@ -334,6 +338,7 @@ fn seed_to_ipv4_string(seed: &str) -> Option<String> {
 pub async fn lp_init(mypubport: u16, ctx: MmArc) -> Result<(), String> {
     info!("Version: {} DT {}", MM_VERSION, MM_DATETIME);
     try_s!(lp_passphrase_init(&ctx));
+    try_s!(validate_ordermatch_conf(&ctx.conf));
 
     try_s!(fix_directories(&ctx));
     #[cfg(not(target_arch = "wasm32"))]
@@ -357,6 +362,7 @@ pub async fn lp_init(mypubport: u16, ctx: MmArc) -> Result<(), String> {
         // an order and start new swap that might get started 2 times because of kick-start
         let mut coins_needed_for_kick_start = swap_kick_starts(ctx.clone());
         coins_needed_for_kick_start.extend(try_s!(orders_kick_start(&ctx).await));
+        coins_needed_for_kick_start.extend(try_s!(matched_swaps_kick_start(&ctx).await));
         *(try_s!(ctx.coins_needed_for_kick_start.lock())) = coins_needed_for_kick_start;
     }
 
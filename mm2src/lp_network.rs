@@ -179,6 +179,19 @@ pub async fn subscribe_to_topic(ctx: &MmArc, topic: String) {
     };
 }
 
+/// Get the peer ids of the relays we are connected to.
+///
+/// # Safety
+///
+/// The function locks the [`MmCtx::p2p_ctx`] mutext.
+pub async fn connected_relays(ctx: &MmArc) -> Result<Vec<String>, String> {
+    let (result_tx, result_rx) = oneshot::channel();
+    let p2p_ctx = P2PContext::fetch_from_mm_arc(ctx);
+    let cmd = AdexBehaviourCmd::GetRelayMesh { result_tx };
+    try_s!(p2p_ctx.cmd_tx.lock().await.try_send(cmd));
+    Ok(try_s!(result_rx.await))
+}
+
 pub async fn request_any_relay<T: de::DeserializeOwned>(
     ctx: MmArc,
     req: P2PRequest,
@@ -207,7 +220,6 @@ pub enum PeerDecodedResponse<T> {
     Err(String),
 }
 
-#[allow(dead_code)]
 pub async fn request_relays<T: de::DeserializeOwned>(
     ctx: MmArc,
     req: P2PRequest,
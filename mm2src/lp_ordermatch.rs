@@ -31,57 +31,115 @@ use common::mm_ctx::{from_ctx, MmArc, MmWeak};
 use common::mm_number::{Fraction, MmNumber};
 use common::{bits256, json_dir_entries, log, new_uuid, now_ms, remove_file, write};
 use derive_more::Display;
+use futures::channel::mpsc::{self, Receiver, Sender};
 use futures::{compat::Future01CompatExt, lock::Mutex as AsyncMutex, StreamExt, TryFutureExt};
 use gstuff::slurp;
 use hash256_std_hasher::Hash256StdHasher;
 use hash_db::{Hasher, EMPTY_PREFIX};
 use http::Response;
-use mm2_libp2p::{decode_signed, encode_and_sign, encode_message, pub_sub_topic, TopicPrefix, TOPIC_SEPARATOR};
+use mm2_libp2p::{decode_signed, encode_and_sign, encode_message, pub_sub_topic, PeerId, TopicPrefix, TOPIC_SEPARATOR};
 #[cfg(test)] use mocktopus::macros::*;
 use num_rational::BigRational;
 use num_traits::identities::Zero;
 use order_requests_tracker::OrderRequestsTracker;
+use orderbook_events::OrderbookEventsSubscriptions;
+use pair_filter::PairFilter;
 use rpc::v1::types::H256 as H256Json;
 use serde_json::{self as json, Value as Json};
 use sp_trie::{delta_trie_root, DBValue, HashDBT, MemoryDB, Trie, TrieConfiguration, TrieDB, TrieDBMut, TrieHash,
               TrieMut};
 use std::collections::hash_map::{Entry, HashMap, RawEntryMut};
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeSet, HashSet, VecDeque};
 use std::convert::TryInto;
 use std::fmt;
 use std::fs::DirEntry;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use swap_outcomes::{record_swap_outcome, SwapOutcome, SwapOutcomesLog};
 use trie_db::NodeCodec as NodeCodecT;
 use uuid::Uuid;
 
-use crate::mm2::lp_network::{broadcast_p2p_msg, request_any_relay, request_one_peer, subscribe_to_topic, P2PRequest};
+use crate::mm2::lp_network::{broadcast_p2p_msg, connected_relays, request_any_relay, request_one_peer, request_relays,
+                             subscribe_to_topic, P2PRequest, PeerDecodedResponse};
 use crate::mm2::lp_swap::{calc_max_maker_vol, check_balance_for_maker_swap, check_balance_for_taker_swap,
-                          check_other_coin_balance_for_swap, insert_new_swap_to_db, is_pubkey_banned,
-                          lp_atomic_locktime, run_maker_swap, run_taker_swap, AtomicLocktimeVersion, MakerSwap,
-                          RunMakerSwapInput, RunTakerSwapInput, SwapConfirmationsSettings, TakerSwap};
+                          check_other_coin_balance_for_swap, insert_new_swap_to_db, is_pubkey_banned, load_my_swap,
+                          lp_atomic_locktime, my_swap_file_path, run_maker_swap, run_taker_swap, running_swaps_num,
+                          AtomicLocktimeVersion, MakerSwap, RunMakerSwapInput, RunTakerSwapInput,
+                          SavedLocktimeVersion, SwapConfirmationsSettings, TakerSwap};
 
 pub use best_orders::best_orders_rpc;
+pub use matchable_pairs::matchable_pairs_rpc;
+pub use order_by_uuid::order_by_uuid_rpc;
 pub use orderbook_depth::orderbook_depth_rpc;
+pub use orderbook_events::{orderbook_events_rpc, orderbook_events_subscribe_rpc};
 pub use orderbook_rpc::orderbook_rpc;
+pub use orderbook_snapshot::{orderbook_snapshot_diff_rpc, orderbook_snapshot_rpc};
+pub use orderbook_stats::orderbook_stats_rpc;
+pub use pair_filter::set_pair_filter_rpc;
+pub use swap_outcomes::swap_outcomes_stats_rpc;
+pub use trading_pair_config::trading_pair_config_rpc;
+pub use update_orderbook::update_orderbook_rpc;
 
 #[path = "lp_ordermatch/best_orders.rs"] mod best_orders;
+#[path = "lp_ordermatch/matchable_pairs.rs"] mod matchable_pairs;
 #[path = "lp_ordermatch/new_protocol.rs"] mod new_protocol;
+#[path = "lp_ordermatch/order_by_uuid.rs"] mod order_by_uuid;
 #[path = "lp_ordermatch/order_requests_tracker.rs"]
 mod order_requests_tracker;
 #[path = "lp_ordermatch/orderbook_depth.rs"] mod orderbook_depth;
+#[path = "lp_ordermatch/orderbook_events.rs"] mod orderbook_events;
 #[path = "lp_ordermatch/orderbook_rpc.rs"] mod orderbook_rpc;
+#[path = "lp_ordermatch/orderbook_snapshot.rs"] mod orderbook_snapshot;
+#[path = "lp_ordermatch/orderbook_stats.rs"] mod orderbook_stats;
 #[cfg(all(test, not(target_arch = "wasm32")))]
 #[path = "ordermatch_tests.rs"]
 mod ordermatch_tests;
+#[path = "lp_ordermatch/pair_filter.rs"] mod pair_filter;
+#[path = "lp_ordermatch/swap_outcomes.rs"] mod swap_outcomes;
+#[path = "lp_ordermatch/trading_pair_config.rs"] mod trading_pair_config;
+#[path = "lp_ordermatch/update_orderbook.rs"] mod update_orderbook;
 
 pub const ORDERBOOK_PREFIX: TopicPrefix = "orbk";
 const MIN_ORDER_KEEP_ALIVE_INTERVAL: u64 = 30;
+/// The delay of our keep-alive broadcasted after a new maker order is created.
+/// The orders created within this delay are advertised by a single keep-alive.
+const MY_KEEP_ALIVE_DEBOUNCE_INTERVAL: f64 = 1.;
 const MAKER_ORDER_TIMEOUT: u64 = MIN_ORDER_KEEP_ALIVE_INTERVAL * 3;
 const TAKER_ORDER_TIMEOUT: u64 = 30;
 const ORDER_MATCH_TIMEOUT: u64 = 30;
 const ORDERBOOK_REQUESTING_TIMEOUT: u64 = MIN_ORDER_KEEP_ALIVE_INTERVAL * 2;
 const MAX_ORDERS_NUMBER_IN_ORDERBOOK_RESPONSE: usize = 1000;
+/// The maximum number of diffs kept in the history of a single pubkey pair trie.
+/// Diffs beyond this horizon are evicted and the full trie is sent to the peer requesting them.
+const TRIE_DIFF_HISTORY_MAX_LEN: usize = 1000;
+/// The version of the [`OrderbookItem`] serialization sent in the orderbook and sync responses.
+/// Must be increased on any change of the `OrderbookItem` fields as the pubkey pair tries roots depend on it.
+const ORDERBOOK_ITEM_VERSION: u8 = 1;
+/// The default limit of orders that a single pubkey can have in our orderbook, can be overridden by `max_orders_per_pubkey` conf.
+/// The limit is disabled by `max_orders_per_pubkey: 0`.
+const MAX_ORDERS_PER_PUBKEY: usize = 1000;
+/// The maximum number of updates buffered for an order whose `MakerOrderCreated` message has not been received yet.
+const MAX_PENDING_UPDATES_PER_ORDER: usize = 10;
+/// The maximum number of unknown orders the updates are buffered for.
+const MAX_PENDING_UPDATES_ORDERS: usize = 1000;
+/// The buffered updates are discarded if the order is not created within this number of seconds.
+const PENDING_UPDATES_TTL: u64 = MIN_ORDER_KEEP_ALIVE_INTERVAL;
+/// The default number of relays that must respond before the requested orderbook is considered synced,
+/// can be overridden by `orderbook_min_relays` conf.
+const ORDERBOOK_MIN_RELAYS: usize = 1;
+/// The minimum interval between the requests of a not synced orderbook from all relays.
+const ORDERBOOK_SYNC_RETRY_INTERVAL: u64 = MIN_ORDER_KEEP_ALIVE_INTERVAL;
+/// The interval of checking whether our node is still connected to the relays.
+const RELAYS_CONNECTIVITY_CHECK_INTERVAL: u64 = MIN_ORDER_KEEP_ALIVE_INTERVAL * 2;
+/// The number of consecutive checks the relay mesh must be empty on to consider our node disconnected.
+const RELAYS_DISCONNECTED_CHECKS: u32 = 3;
+/// The default maximum number of confirmations a taker request can set for a swap coin,
+/// can be overridden by `max_taker_request_confs` conf.
+const MAX_TAKER_REQUEST_CONFS: u64 = 100;
+/// The max number of the orderbook events buffered for a subscriber.
+/// The subscriber is dropped once its buffer is full, so it has to resubscribe and reload the orderbook.
+const ORDERBOOK_EVENTS_BUFFER: usize = 1000;
 
 /// Alphabetically ordered orderbook pair
 type AlbOrderedOrderbookPair = String;
@@ -113,7 +171,9 @@ fn process_pubkey_full_trie(
     remove_and_purge_pubkey_pair_orders(orderbook, pubkey, alb_pair);
 
     for (_uuid, order) in new_trie_orders {
-        orderbook.insert_or_update_order_update_trie(order);
+        if is_valid_order_pair(pubkey, &order.base, &order.rel) {
+            orderbook.insert_or_update_order_update_trie(order);
+        }
     }
 
     let new_root = pubkey_state_mut(&mut orderbook.pubkeys_state, pubkey)
@@ -132,7 +192,11 @@ fn process_trie_delta(
 ) -> H64 {
     for (uuid, order) in delta_orders {
         match order {
-            Some(order) => orderbook.insert_or_update_order_update_trie(order),
+            Some(order) => {
+                if is_valid_order_pair(pubkey, &order.base, &order.rel) {
+                    orderbook.insert_or_update_order_update_trie(order);
+                }
+            },
             None => {
                 orderbook.remove_order_trie_update(uuid);
             },
@@ -170,14 +234,20 @@ async fn process_orders_keep_alive(
         None => return true,
     };
 
-    let resp =
-        request_one_peer::<SyncPubkeyOrderbookStateRes>(ctx.clone(), P2PRequest::Ordermatch(req), propagated_from_peer)
-            .await;
+    let resp = request_one_peer::<SyncPubkeyOrderbookStateRes>(
+        ctx.clone(),
+        P2PRequest::Ordermatch(req),
+        propagated_from_peer.clone(),
+    )
+    .await;
 
     let response = match resp {
         Ok(Some(resp)) => resp,
         _ => return false,
     };
+    if !is_orderbook_item_version_supported(response.orderbook_item_version, &propagated_from_peer) {
+        return false;
+    }
 
     let mut orderbook = ordermatch_ctx.orderbook.lock().await;
     for (pair, diff) in response.pair_orders_diff {
@@ -189,6 +259,40 @@ async fn process_orders_keep_alive(
     true
 }
 
+async fn process_maker_order_created(
+    ctx: MmArc,
+    from_pubkey: String,
+    created_msg: new_protocol::MakerOrderCreated,
+) -> bool {
+    if !is_valid_order_pair(&from_pubkey, &created_msg.base, &created_msg.rel) {
+        return false;
+    }
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).expect("from_ctx failed");
+    let mut order: OrderbookItem = (created_msg, from_pubkey).into();
+    let mut orderbook = ordermatch_ctx.orderbook.lock().await;
+    match orderbook.find_order_by_uuid(&order.uuid) {
+        // the uuid is already taken by another pubkey, the order must not be overwritten
+        Some(existing) if existing.pubkey != order.pubkey => {
+            log::warn!(
+                "Order {} from pubkey {} is already known from pubkey {}, ignoring",
+                order.uuid,
+                order.pubkey,
+                existing.pubkey
+            );
+            false
+        },
+        _ => {
+            let uuid = order.uuid;
+            for update in orderbook.take_pending_updates(&uuid, &order.pubkey) {
+                order.apply_updated(&update);
+            }
+            orderbook.insert_or_update_order_update_trie(order);
+            // the order might be evicted immediately if the pubkey exceeds the orders limit, do not rebroadcast it then
+            orderbook.order_set.contains_key(&uuid)
+        },
+    }
+}
+
 async fn process_maker_order_updated(
     ctx: MmArc,
     from_pubkey: String,
@@ -204,10 +308,11 @@ async fn process_maker_order_updated(
             true
         },
         None => {
-            log::warn!(
-                "Couldn't find an order {}, ignoring, it will be synced upon pubkey keep alive",
+            log::debug!(
+                "Couldn't find an order {}, the update will be applied once the order is created",
                 uuid
             );
+            orderbook.add_pending_update(&from_pubkey, updated_msg);
             false
         },
     }
@@ -235,42 +340,179 @@ async fn process_maker_order_updated(
 ///
 /// The function locks [`MmCtx::p2p_ctx`] and [`MmCtx::ordermatch_ctx`]
 async fn request_and_fill_orderbook(ctx: &MmArc, base: &str, rel: &str) -> Result<(), String> {
+    let (responses, relays_requested) = try_s!(request_orderbook_from_relays(ctx, base, rel).await);
+    if responses.is_empty() {
+        return Ok(());
+    }
+
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+    let mut orderbook = ordermatch_ctx.orderbook.lock().await;
+    fill_orderbook(
+        ctx,
+        &ordermatch_ctx,
+        &mut orderbook,
+        base,
+        rel,
+        responses,
+        relays_requested,
+    );
+    Ok(())
+}
+
+/// Requests the `base`/`rel` orderbook from relays.
+/// Returns the responses with the relays they are received from and the number of the relays requested.
+///
+/// # Safety
+///
+/// The function locks [`MmCtx::p2p_ctx`] and [`MmCtx::ordermatch_ctx`]
+async fn request_orderbook_from_relays(
+    ctx: &MmArc,
+    base: &str,
+    rel: &str,
+) -> Result<(Vec<(GetOrderbookRes, PeerId)>, usize), String> {
     let request = OrdermatchRequest::GetOrderbook {
         base: base.to_string(),
         rel: rel.to_string(),
     };
 
-    let response = try_s!(request_any_relay::<GetOrderbookRes>(ctx.clone(), P2PRequest::Ordermatch(request)).await);
-    let pubkey_orders = match response {
-        Some((GetOrderbookRes { pubkey_orders }, _peer_id)) => pubkey_orders,
-        None => return Ok(()),
-    };
-
     let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
-    let mut orderbook = ordermatch_ctx.orderbook.lock().await;
+    let min_relays = ordermatch_ctx.orderbook.lock().await.min_sync_relays;
+    // request all relays only if we need more than one of them to respond
+    if min_relays > 1 {
+        let responses = try_s!(request_relays::<GetOrderbookRes>(ctx.clone(), P2PRequest::Ordermatch(request)).await);
+        let relays_requested = responses.len();
+        let responses = responses
+            .into_iter()
+            .filter_map(|(peer_id, response)| match response {
+                PeerDecodedResponse::Ok(response) => Some((response, peer_id)),
+                PeerDecodedResponse::None => None,
+                PeerDecodedResponse::Err(e) => {
+                    log::warn!("Error {} on orderbook request to {}", e, peer_id);
+                    None
+                },
+            })
+            .collect();
+        Ok((responses, relays_requested))
+    } else {
+        let response = try_s!(request_any_relay::<GetOrderbookRes>(ctx.clone(), P2PRequest::Ordermatch(request)).await);
+        Ok((response.into_iter().collect(), 1))
+    }
+}
 
+/// Fills the `orderbook` with the orders of the relays `responses`.
+/// The `base`/`rel` orderbook is marked as requested once the required number of relays responded.
+fn fill_orderbook(
+    ctx: &MmArc,
+    ordermatch_ctx: &OrdermatchContext,
+    orderbook: &mut Orderbook,
+    base: &str,
+    rel: &str,
+    responses: Vec<(GetOrderbookRes, PeerId)>,
+    relays_requested: usize,
+) {
     let alb_pair = alb_ordered_pair(base, rel);
-    for (pubkey, GetOrderbookPubkeyItem { orders, .. }) in pubkey_orders {
-        let pubkey_bytes = match hex::decode(&pubkey) {
-            Ok(b) => b,
-            Err(e) => {
-                log::warn!("Error {} decoding pubkey {}", e, pubkey);
-                continue;
-            },
-        };
-        if is_pubkey_banned(ctx, &pubkey_bytes[1..].into()) {
-            log::warn!("Pubkey {} is banned", pubkey);
+    let mut relays_responded = Vec::with_capacity(responses.len());
+    for (
+        GetOrderbookRes {
+            pubkey_orders,
+            orderbook_item_version,
+        },
+        peer_id,
+    ) in responses
+    {
+        if !is_orderbook_item_version_supported(orderbook_item_version, &peer_id.to_string()) {
             continue;
         }
-        let _new_root = process_pubkey_full_trie(&mut orderbook, &pubkey, &alb_pair, orders);
+        for (pubkey, GetOrderbookPubkeyItem { orders, .. }) in pubkey_orders {
+            let pubkey_bytes = match hex::decode(&pubkey) {
+                Ok(b) => b,
+                Err(e) => {
+                    log::warn!("Error {} decoding pubkey {}", e, pubkey);
+                    continue;
+                },
+            };
+            if is_pubkey_banned(ctx, &pubkey_bytes[1..].into()) {
+                log::warn!("Pubkey {} is banned", pubkey);
+                continue;
+            }
+            if !ordermatch_ctx.is_maker_pubkey_allowed(&pubkey) {
+                continue;
+            }
+            let _new_root = process_pubkey_full_trie(orderbook, &pubkey, &alb_pair, orders);
+        }
+        relays_responded.push(peer_id.to_string());
     }
 
     let topic = orderbook_topic_from_base_rel(base, rel);
+    if !orderbook.record_sync_relays(&topic, relays_responded, relays_requested) {
+        log::warn!(
+            "Orderbook {} is not synced yet, only {} of {} required relays responded",
+            topic,
+            orderbook.sync_relays[&topic].responded.len(),
+            orderbook.min_sync_relays
+        );
+        return;
+    }
     orderbook
         .topics_subscribed_to
         .insert(topic, OrderbookRequestingState::Requested);
+}
 
-    Ok(())
+/// Requests the `base`/`rel` orderbook again and replaces the pair orders of all pubkeys except ours with the received ones.
+/// The orders are purged only if the required number of relays responded.
+/// Returns the number of the pair orders in both directions after the resync.
+///
+/// # Safety
+///
+/// The function locks [`MmCtx::p2p_ctx`] and [`MmCtx::ordermatch_ctx`]
+async fn resync_orderbook(ctx: &MmArc, base: &str, rel: &str) -> Result<usize, String> {
+    let request_orderbook = false;
+    try_s!(subscribe_to_orderbook_topic(ctx, base, rel, request_orderbook).await);
+    let (responses, relays_requested) = try_s!(request_orderbook_from_relays(ctx, base, rel).await);
+
+    let ordermatch_ctx = try_s!(OrdermatchContext::from_ctx(ctx));
+    let mut orderbook = ordermatch_ctx.orderbook.lock().await;
+    let min_relays = orderbook.min_sync_relays.max(1);
+    if responses.len() < min_relays {
+        return ERR!(
+            "Only {} of {} required relays responded, the orderbook {}/{} is kept as is",
+            responses.len(),
+            min_relays,
+            base,
+            rel
+        );
+    }
+
+    let my_pubsecp = hex::encode(&**ctx.secp256k1_key_pair().public());
+    let alb_pair = alb_ordered_pair(base, rel);
+    let pubkeys: Vec<_> = orderbook
+        .pubkeys_state
+        .iter()
+        .filter(|(pubkey, state)| **pubkey != my_pubsecp && state.trie_roots.contains_key(&alb_pair))
+        .map(|(pubkey, _)| pubkey.clone())
+        .collect();
+    for pubkey in pubkeys {
+        remove_and_purge_pubkey_pair_orders(&mut orderbook, &pubkey, &alb_pair);
+    }
+    // the orderbook is confirmed by the responded relays only
+    orderbook.sync_relays.remove(&orderbook_topic_from_base_rel(base, rel));
+    fill_orderbook(
+        ctx,
+        &ordermatch_ctx,
+        &mut orderbook,
+        base,
+        rel,
+        responses,
+        relays_requested,
+    );
+
+    let orders_num = |base: &str, rel: &str| {
+        orderbook
+            .unordered
+            .get(&(base.to_owned(), rel.to_owned()))
+            .map_or(0, |uuids| uuids.len())
+    };
+    Ok(orders_num(base, rel) + orders_num(rel, base))
 }
 
 /// Insert or update an order `req`.
@@ -352,11 +594,22 @@ pub async fn process_msg(ctx: MmArc, _topics: Vec<String>, from_peer: String, ms
                 log::warn!("Pubkey {} is banned", pubkey.to_hex());
                 return false;
             }
+            let is_maker_message = matches!(
+                message,
+                new_protocol::OrdermatchMessage::MakerOrderCreated(_)
+                    | new_protocol::OrdermatchMessage::MakerOrderUpdated(_)
+                    | new_protocol::OrdermatchMessage::PubkeyKeepAlive(_)
+            );
+            if is_maker_message {
+                let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).expect("from_ctx failed");
+                if !ordermatch_ctx.is_maker_pubkey_allowed(&pubkey.to_hex()) {
+                    log::debug!("Pubkey {} is not in the maker pubkeys allowlist", pubkey.to_hex());
+                    return false;
+                }
+            }
             match message {
                 new_protocol::OrdermatchMessage::MakerOrderCreated(created_msg) => {
-                    let order: OrderbookItem = (created_msg, hex::encode(pubkey.to_bytes().as_slice())).into();
-                    insert_or_update_order(&ctx, order).await;
-                    true
+                    process_maker_order_created(ctx, pubkey.to_hex(), created_msg).await
                 },
                 new_protocol::OrdermatchMessage::PubkeyKeepAlive(keep_alive) => {
                     process_orders_keep_alive(ctx, from_peer, pubkey.to_hex(), keep_alive, i_am_relay).await
@@ -488,6 +741,26 @@ struct GetOrderbookPubkeyItem {
 struct GetOrderbookRes {
     /// Asks and bids grouped by pubkey.
     pubkey_orders: HashMap<String, GetOrderbookPubkeyItem>,
+    /// The version of the [`OrderbookItem`] serialization, it's not sent by the peers using the version 1.
+    #[serde(default = "legacy_orderbook_item_version")]
+    orderbook_item_version: u8,
+}
+
+fn legacy_orderbook_item_version() -> u8 { 1 }
+
+/// Whether the orders sent by the `peer` can be processed by this node.
+/// The orders of another version would produce the pubkey pair tries roots differing from the ones the maker advertises.
+fn is_orderbook_item_version_supported(version: u8, peer: &str) -> bool {
+    if version != ORDERBOOK_ITEM_VERSION {
+        log::warn!(
+            "Peer {} sent the orders of unsupported version {}, expected {}",
+            peer,
+            version,
+            ORDERBOOK_ITEM_VERSION
+        );
+        return false;
+    }
+    true
 }
 
 async fn process_get_orderbook_request(ctx: MmArc, base: String, rel: String) -> Result<Option<Vec<u8>>, String> {
@@ -543,7 +816,10 @@ async fn process_get_orderbook_request(ctx: MmArc, base: String, rel: String) ->
         .collect();
 
     let pubkey_orders = orders_to_send?;
-    let response = GetOrderbookRes { pubkey_orders };
+    let response = GetOrderbookRes {
+        pubkey_orders,
+        orderbook_item_version: ORDERBOOK_ITEM_VERSION,
+    };
     let encoded = try_s!(encode_message(&response));
     Ok(Some(encoded))
 }
@@ -629,6 +905,9 @@ struct SyncPubkeyOrderbookStateRes {
     /// last signed OrdermatchMessage payload from pubkey
     last_signed_pubkey_payload: Vec<u8>,
     pair_orders_diff: HashMap<AlbOrderedOrderbookPair, DeltaOrFullTrie<Uuid, OrderbookItem>>,
+    /// The version of the [`OrderbookItem`] serialization, it's not sent by the peers using the version 1.
+    #[serde(default = "legacy_orderbook_item_version")]
+    orderbook_item_version: u8,
 }
 
 async fn process_sync_pubkey_orderbook_state(
@@ -666,6 +945,7 @@ async fn process_sync_pubkey_orderbook_state(
     let result = SyncPubkeyOrderbookStateRes {
         last_signed_pubkey_payload,
         pair_orders_diff,
+        orderbook_item_version: ORDERBOOK_ITEM_VERSION,
     };
     Ok(Some(result))
 }
@@ -684,6 +964,44 @@ fn orderbook_topic_from_base_rel(base: &str, rel: &str) -> String {
 
 fn orderbook_topic_from_ordered_pair(pair: &str) -> String { pub_sub_topic(ORDERBOOK_PREFIX, pair) }
 
+/// Resolves the user supplied `ticker` to the ticker of the coins config ignoring the case and the surrounding whitespaces,
+/// so "kmd" or " KMD" don't fragment the orderbook and the tries of the "KMD" coin.
+/// Returns the trimmed `ticker` if there is no such coin in the config.
+fn canonical_ticker(ctx: &MmArc, ticker: &str) -> String { canonical_ticker_from_conf(&ctx.conf, ticker) }
+
+/// Same as [`canonical_ticker`], but resolves the `ticker` by the `coins` of the given config.
+fn canonical_ticker_from_conf(conf: &Json, ticker: &str) -> String {
+    let ticker = ticker.trim();
+    let configured: Vec<&str> = match conf["coins"].as_array() {
+        Some(coins) => coins.iter().filter_map(|coin| coin["coin"].as_str()).collect(),
+        None => return ticker.to_owned(),
+    };
+    let exact = configured.iter().find(|coin| **coin == ticker);
+    match exact.or_else(|| configured.iter().find(|coin| coin.eq_ignore_ascii_case(ticker))) {
+        Some(coin) => (*coin).to_owned(),
+        None => ticker.to_owned(),
+    }
+}
+
+/// Whether the `ticker` received from the network can form the orderbook pair, see [`alb_ordered_pair`].
+fn is_valid_pair_ticker(ticker: &str) -> bool {
+    !ticker.is_empty() && !ticker.contains(|c: char| c.is_whitespace() || c == ':')
+}
+
+/// Checks the pair of the order received from the `pubkey` before it's inserted to the orderbook.
+fn is_valid_order_pair(pubkey: &str, base: &str, rel: &str) -> bool {
+    if is_valid_pair_ticker(base) && is_valid_pair_ticker(rel) {
+        return true;
+    }
+    log::warn!(
+        "Order from pubkey {} has invalid pair {:?}/{:?}, ignoring",
+        pubkey,
+        base,
+        rel
+    );
+    false
+}
+
 #[test]
 fn test_alb_ordered_pair() {
     assert_eq!("BTC:KMD", alb_ordered_pair("KMD", "BTC"));
@@ -691,7 +1009,6 @@ fn test_alb_ordered_pair() {
     assert_eq!("KMD:QTUM", alb_ordered_pair("QTUM", "KMD"));
 }
 
-#[allow(dead_code)]
 fn parse_orderbook_pair_from_topic(topic: &str) -> Option<(&str, &str)> {
     let mut split = topic.split(|maybe_sep| maybe_sep == TOPIC_SEPARATOR);
     match split.next() {
@@ -723,25 +1040,15 @@ fn test_parse_orderbook_pair_from_topic() {
 
 async fn maker_order_created_p2p_notify(ctx: MmArc, order: &MakerOrder) {
     let topic = orderbook_topic_from_base_rel(&order.base, &order.rel);
-    let message = new_protocol::MakerOrderCreated {
-        uuid: order.uuid.into(),
-        base: order.base.clone(),
-        rel: order.rel.clone(),
-        price: order.price.to_ratio(),
-        max_volume: order.available_amount().to_ratio(),
-        min_volume: order.min_base_vol.to_ratio(),
-        conf_settings: order.conf_settings.unwrap(),
-        created_at: now_ms() / 1000,
-        timestamp: now_ms() / 1000,
-        pair_trie_root: H64::default(),
-    };
-
     let key_pair = ctx.secp256k1_key_pair.or(&&|| panic!());
-    let to_broadcast = new_protocol::OrdermatchMessage::MakerOrderCreated(message.clone());
+    let item = order.to_orderbook_item(hex::encode(&**key_pair.public()));
+    let message = item.to_maker_order_created(order.conf_settings.unwrap());
+
+    let to_broadcast = new_protocol::OrdermatchMessage::MakerOrderCreated(message);
     let encoded_msg = encode_and_sign(&to_broadcast, &*key_pair.private().secret).unwrap();
-    let order: OrderbookItem = (message, hex::encode(&**key_pair.public())).into();
-    insert_or_update_order(&ctx, order).await;
+    insert_or_update_order(&ctx, item).await;
     broadcast_p2p_msg(&ctx, vec![topic], encoded_msg);
+    schedule_my_keep_alive(&ctx);
 }
 
 async fn process_my_maker_order_updated(ctx: &MmArc, message: &new_protocol::MakerOrderUpdated) {
@@ -764,6 +1071,15 @@ async fn maker_order_updated_p2p_notify(ctx: MmArc, base: &str, rel: &str, messa
     broadcast_p2p_msg(&ctx, vec![topic], encoded_msg);
 }
 
+fn maker_order_filled_notify(ctx: &MmArc, filled: &MakerOrderFilled) {
+    let uuid = filled.uuid.to_string();
+    ctx.log.log(
+        "🤝",
+        &[&"order", &("uuid", uuid.as_str()), &"filled"],
+        &json::to_string(filled).expect("Serialization failed"),
+    );
+}
+
 async fn maker_order_cancelled_p2p_notify(ctx: MmArc, order: &MakerOrder) {
     let message = new_protocol::OrdermatchMessage::MakerOrderCancelled(new_protocol::MakerOrderCancelled {
         uuid: order.uuid.into(),
@@ -928,6 +1244,15 @@ impl TakerRequest {
     fn get_base_amount(&self) -> &MmNumber { &self.base_amount }
 
     fn get_rel_amount(&self) -> &MmNumber { &self.rel_amount }
+
+    /// The `base`/`rel` pair of the maker order this request would be converted to, the coin we sell goes first.
+    /// The [`PairFilter`] is checked against this pair so the taker and maker orders are filtered the same way.
+    fn maker_pair(&self) -> (&str, &str) {
+        match self.action {
+            TakerAction::Sell => (&self.base, &self.rel),
+            TakerAction::Buy => (&self.rel, &self.base),
+        }
+    }
 }
 
 pub struct TakerOrderBuilder<'a> {
@@ -1520,6 +1845,21 @@ fn zero_rat() -> BigRational { BigRational::zero() }
 impl MakerOrder {
     fn available_amount(&self) -> MmNumber { &self.max_base_vol - &self.reserved_amount() }
 
+    /// Builds the public representation of the order that is inserted to our orderbook.
+    /// The `MakerOrderCreated` broadcast payload is derived from it by [`OrderbookItem::to_maker_order_created`].
+    fn to_orderbook_item(&self, pubkey: String) -> OrderbookItem {
+        OrderbookItem {
+            pubkey,
+            base: self.base.clone(),
+            rel: self.rel.clone(),
+            price: self.price.to_ratio(),
+            max_volume: self.available_amount().to_ratio(),
+            min_volume: self.min_base_vol.to_ratio(),
+            uuid: self.uuid,
+            created_at: now_ms() / 1000,
+        }
+    }
+
     fn reserved_amount(&self) -> MmNumber {
         self.matches.iter().fold(
             MmNumber::from(BigRational::from_integer(0.into())),
@@ -1598,6 +1938,40 @@ impl MakerOrder {
 
         self.updated_at = Some(now_ms());
     }
+
+    /// Returns the fill summary if the order volume has been consumed by the started swaps,
+    /// so it's distinguishable from the order removed because of the insufficient balance.
+    fn filled(&self) -> Option<MakerOrderFilled> {
+        if self.matches.is_empty() || self.has_ongoing_matches() || self.available_amount() >= self.min_base_vol {
+            return None;
+        }
+
+        let mut base_vol = MmNumber::from(0);
+        let mut rel_vol = MmNumber::from(0);
+        for order_match in self.matches.values() {
+            base_vol = &base_vol + order_match.reserved.get_base_amount();
+            rel_vol = &rel_vol + order_match.reserved.get_rel_amount();
+        }
+        Some(MakerOrderFilled {
+            uuid: self.uuid,
+            base: self.base.clone(),
+            rel: self.rel.clone(),
+            filled_base_vol: base_vol.into(),
+            filled_rel_vol: rel_vol.into(),
+            matches_count: self.matches.len(),
+        })
+    }
+}
+
+/// The event emitted when our maker order is completely filled.
+#[derive(Debug, PartialEq, Serialize)]
+struct MakerOrderFilled {
+    uuid: Uuid,
+    base: String,
+    rel: String,
+    filled_base_vol: BigDecimal,
+    filled_rel_vol: BigDecimal,
+    matches_count: usize,
 }
 
 impl Into<MakerOrder> for TakerOrder {
@@ -1752,33 +2126,53 @@ impl Into<new_protocol::OrdermatchMessage> for MakerConnected {
 }
 
 pub async fn broadcast_maker_orders_keep_alive_loop(ctx: MmArc) {
-    let my_pubsecp = hex::encode(&**ctx.secp256k1_key_pair().public());
     while !ctx.is_stopping() {
         Timer::sleep(MIN_ORDER_KEEP_ALIVE_INTERVAL as f64).await;
-        let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).expect("from_ctx failed");
-        let orderbook = ordermatch_ctx.orderbook.lock().await;
-        let state = match orderbook.pubkeys_state.get(&my_pubsecp) {
-            Some(s) => s,
-            None => continue,
-        };
+        broadcast_my_keep_alive(&ctx).await;
+    }
+}
 
-        let mut trie_roots = HashMap::new();
-        let mut topics = HashSet::new();
-        for (alb_pair, root) in state.trie_roots.iter() {
-            if *root == H64::default() && *root == hashed_null_node::<Layout>() {
-                continue;
-            }
-            topics.insert(orderbook_topic_from_ordered_pair(alb_pair));
-            trie_roots.insert(alb_pair.clone(), *root);
-        }
+/// Broadcasts our keep-alive shortly after a new maker order is created instead of waiting for
+/// the next [`broadcast_maker_orders_keep_alive_loop`] iteration, so the peers learn our new trie roots faster.
+fn schedule_my_keep_alive(ctx: &MmArc) {
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).expect("from_ctx failed");
+    if ordermatch_ctx.my_keep_alive_scheduled.swap(true, Ordering::AcqRel) {
+        return;
+    }
 
-        let message = new_protocol::PubkeyKeepAlive {
-            trie_roots,
-            timestamp: now_ms() / 1000,
-        };
+    let ctx = ctx.clone();
+    spawn(async move {
+        Timer::sleep(MY_KEEP_ALIVE_DEBOUNCE_INTERVAL).await;
+        ordermatch_ctx.my_keep_alive_scheduled.store(false, Ordering::Release);
+        broadcast_my_keep_alive(&ctx).await;
+    });
+}
 
-        broadcast_ordermatch_message(&ctx, topics, message.into());
+async fn broadcast_my_keep_alive(ctx: &MmArc) {
+    let my_pubsecp = hex::encode(&**ctx.secp256k1_key_pair().public());
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).expect("from_ctx failed");
+    let orderbook = ordermatch_ctx.orderbook.lock().await;
+    let state = match orderbook.pubkeys_state.get(&my_pubsecp) {
+        Some(s) => s,
+        None => return,
+    };
+
+    let mut trie_roots = HashMap::new();
+    let mut topics = HashSet::new();
+    for (alb_pair, root) in state.trie_roots.iter() {
+        if *root == H64::default() && *root == hashed_null_node::<Layout>() {
+            continue;
+        }
+        topics.insert(orderbook_topic_from_ordered_pair(alb_pair));
+        trie_roots.insert(alb_pair.clone(), *root);
     }
+
+    let message = new_protocol::PubkeyKeepAlive {
+        trie_roots,
+        timestamp: now_ms() / 1000,
+    };
+
+    broadcast_ordermatch_message(&ctx, topics, message.into());
 }
 
 fn broadcast_ordermatch_message(
@@ -1818,12 +2212,15 @@ struct TrieDiff<Key, Value> {
 #[derive(Debug, Eq, PartialEq)]
 struct TrieDiffHistory<Key, Value> {
     inner: HashMap<H64, TrieDiff<Key, Value>>,
+    /// The keys of `inner` from the oldest to the newest diff
+    insertion_order: VecDeque<H64>,
 }
 
 impl<Key, Value> Default for TrieDiffHistory<Key, Value> {
     fn default() -> Self {
         TrieDiffHistory {
             inner: Default::default(),
+            insertion_order: Default::default(),
         }
     }
 }
@@ -1842,13 +2239,31 @@ impl<Key, Value> TrieDiffHistory<Key, Value> {
                 while let Some(next_diff) = self.inner.remove(&diff.next_root) {
                     diff = next_diff;
                 }
+                let inner = &self.inner;
+                self.insertion_order.retain(|key| inner.contains_key(key));
             },
             None => {
-                self.inner.insert(insert_at, diff);
+                if self.inner.insert(insert_at, diff).is_some() {
+                    self.insertion_order.retain(|key| *key != insert_at);
+                }
+                self.insertion_order.push_back(insert_at);
+                self.evict_oldest_diffs();
             },
         };
     }
 
+    /// Removes the oldest diffs exceeding the [`TRIE_DIFF_HISTORY_MAX_LEN`] horizon.
+    fn evict_oldest_diffs(&mut self) {
+        while self.inner.len() > TRIE_DIFF_HISTORY_MAX_LEN {
+            match self.insertion_order.pop_front() {
+                Some(key) => {
+                    self.inner.remove(&key);
+                },
+                None => break,
+            }
+        }
+    }
+
     #[allow(dead_code)]
     fn remove_key(&mut self, key: &H64) { self.inner.remove(key); }
 
@@ -1946,6 +2361,7 @@ fn collect_orderbook_metrics(ctx: &MmArc, orderbook: &Orderbook) {
     let memory_db_size = malloc_size(&orderbook.memory_db);
     mm_gauge!(ctx.metrics, "orderbook.len", orderbook.order_set.len() as i64);
     mm_gauge!(ctx.metrics, "orderbook.memory_db", memory_db_size as i64);
+    mm_gauge!(ctx.metrics, "orderbook.evicted_orders", orderbook.evicted_orders as i64);
     // mm_gauge!(ctx.metrics, "inactive_orders.len", inactive.len() as i64);
 
     // TODO remove metrics below after testing
@@ -1971,11 +2387,305 @@ struct Orderbook {
     topics_subscribed_to: HashMap<String, OrderbookRequestingState>,
     /// MemoryDB instance to store Patricia Tries data
     memory_db: MemoryDB<Blake2Hasher64>,
+    /// The maximum number of orders kept per pubkey except ours, None if the limit is disabled by the config
+    max_orders_per_pubkey: Option<usize>,
+    /// Our pubkey, its orders are never limited by `max_orders_per_pubkey`
+    my_pubsecp: Option<String>,
+    /// The number of orders evicted due to the `max_orders_per_pubkey` limit
+    evicted_orders: u64,
+    /// The updates of the orders that are not known yet
+    pending_updates: HashMap<Uuid, PendingOrderUpdates>,
+    /// The minimum number of relays that must respond before the requested orderbook is considered synced
+    min_sync_relays: usize,
+    /// The relays that contributed to the requested orderbooks, by topic
+    sync_relays: HashMap<String, OrderbookSyncRelays>,
+    events_subscribers: Vec<OrderbookEventsSubscriber>,
+}
+
+/// The change of the orderbook, sent to the subscribers of [`Orderbook::subscribe_events`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", content = "data")]
+enum OrderbookEvent {
+    OrderInserted(OrderbookItem),
+    OrderUpdated(OrderbookItem),
+    OrderRemoved(OrderbookItem),
+}
+
+impl OrderbookEvent {
+    fn order(&self) -> &OrderbookItem {
+        match self {
+            OrderbookEvent::OrderInserted(order)
+            | OrderbookEvent::OrderUpdated(order)
+            | OrderbookEvent::OrderRemoved(order) => order,
+        }
+    }
+}
+
+struct OrderbookEventsSubscriber {
+    /// Only the events of the orders of this pair are sent if set, the pair matches both asks and bids
+    pair: Option<(String, String)>,
+    tx: Sender<OrderbookEvent>,
+}
+
+impl OrderbookEventsSubscriber {
+    fn is_interested(&self, order: &OrderbookItem) -> bool {
+        match &self.pair {
+            Some((base, rel)) => {
+                (&order.base == base && &order.rel == rel) || (&order.base == rel && &order.rel == base)
+            },
+            None => true,
+        }
+    }
+}
+
+/// The relays that responded to the orderbook requests of a topic.
+#[derive(Debug, Default)]
+struct OrderbookSyncRelays {
+    responded: HashSet<String>,
+    expected: usize,
+    /// The timestamp of the last request of the orderbook from all relays
+    requested_at: Option<u64>,
+}
+
+impl OrderbookSyncRelays {
+    /// The share of the expected relays that responded, from 0 to 1.
+    fn confidence(&self) -> f64 {
+        if self.expected == 0 {
+            return 0.;
+        }
+        (self.responded.len().min(self.expected)) as f64 / self.expected as f64
+    }
+}
+
+/// The liquidity of our orderbook available to a taker request.
+#[derive(Debug, PartialEq)]
+struct TakerLiquidity {
+    /// Whether there is at least one maker order that can fill the whole request
+    matchable: bool,
+    /// The total `base` coin volume of the maker orders at the requested price or better
+    depth: MmNumber,
+}
+
+/// The updates received before the corresponding `MakerOrderCreated` message.
+struct PendingOrderUpdates {
+    from_pubkey: String,
+    received_at: u64,
+    updates: Vec<new_protocol::MakerOrderUpdated>,
 }
 
 fn hashed_null_node<T: TrieConfiguration>() -> TrieHash<T> { <T::Codec as NodeCodecT>::hashed_null_node() }
 
 impl Orderbook {
+    /// Subscribes to the insertions, updates and removals of the orders received from the network and created by us.
+    fn subscribe_events(&mut self, pair: Option<(String, String)>) -> Receiver<OrderbookEvent> {
+        let (tx, rx) = mpsc::channel(ORDERBOOK_EVENTS_BUFFER);
+        self.events_subscribers.push(OrderbookEventsSubscriber { pair, tx });
+        rx
+    }
+
+    /// Sends the event to the interested subscribers.
+    /// The dropped subscribers and the ones whose buffer is full are removed.
+    fn notify_event(&mut self, event: OrderbookEvent) {
+        let subscribers = std::mem::take(&mut self.events_subscribers);
+        self.events_subscribers = subscribers
+            .into_iter()
+            .filter_map(|mut subscriber| {
+                if !subscriber.is_interested(event.order()) {
+                    return if subscriber.tx.is_closed() {
+                        None
+                    } else {
+                        Some(subscriber)
+                    };
+                }
+                match subscriber.tx.try_send(event.clone()) {
+                    Ok(()) => Some(subscriber),
+                    Err(e) if e.is_full() => {
+                        log::warn!("The orderbook events subscriber lags behind, unsubscribing it");
+                        None
+                    },
+                    Err(_disconnected) => None,
+                }
+            })
+            .collect();
+    }
+
+    /// Buffers the update of the unknown order to apply it once the order is created.
+    fn add_pending_update(&mut self, from_pubkey: &str, update: new_protocol::MakerOrderUpdated) {
+        let now = now_ms() / 1000;
+        self.remove_expired_pending_updates(now);
+
+        let uuid = update.uuid();
+        if !self.pending_updates.contains_key(&uuid) && self.pending_updates.len() >= MAX_PENDING_UPDATES_ORDERS {
+            log::warn!("Too many orders with pending updates, ignoring the update of {}", uuid);
+            return;
+        }
+        let pending = self.pending_updates.entry(uuid).or_insert_with(|| PendingOrderUpdates {
+            from_pubkey: from_pubkey.to_owned(),
+            received_at: now,
+            updates: Vec::new(),
+        });
+        if pending.from_pubkey != from_pubkey {
+            log::warn!(
+                "Order {} has pending updates from pubkey {}, ignoring the update from {}",
+                uuid,
+                pending.from_pubkey,
+                from_pubkey
+            );
+            return;
+        }
+        if pending.updates.len() >= MAX_PENDING_UPDATES_PER_ORDER {
+            log::warn!("Too many pending updates of the order {}, ignoring", uuid);
+            return;
+        }
+        pending.updates.push(update);
+    }
+
+    /// Removes and returns the updates buffered for the order created by the given pubkey.
+    fn take_pending_updates(&mut self, uuid: &Uuid, pubkey: &str) -> Vec<new_protocol::MakerOrderUpdated> {
+        self.remove_expired_pending_updates(now_ms() / 1000);
+        match self.pending_updates.remove(uuid) {
+            Some(pending) if pending.from_pubkey == pubkey => pending.updates,
+            _ => Vec::new(),
+        }
+    }
+
+    /// Records the relays that responded to the orderbook request of the `topic`.
+    /// Returns whether the number of distinct relays responded to the requests reached [`Orderbook::min_sync_relays`].
+    fn record_sync_relays(&mut self, topic: &str, responded: Vec<String>, requested: usize) -> bool {
+        let min_relays = self.min_sync_relays.max(1);
+        let sync_relays = self.sync_relays.entry(topic.to_owned()).or_default();
+        sync_relays.responded.extend(responded);
+        sync_relays.expected = sync_relays.expected.max(requested).max(min_relays);
+        sync_relays.responded.len() >= min_relays
+    }
+
+    /// Records the request of the `topic` orderbook from all relays at `now`.
+    /// Returns false if the orderbook was requested less than [`ORDERBOOK_SYNC_RETRY_INTERVAL`] seconds ago,
+    /// the request must be skipped then.
+    fn try_start_sync_request(&mut self, topic: &str, now: u64) -> bool {
+        let sync_relays = self.sync_relays.entry(topic.to_owned()).or_default();
+        match sync_relays.requested_at {
+            Some(requested_at) if requested_at + ORDERBOOK_SYNC_RETRY_INTERVAL > now => false,
+            _ => {
+                sync_relays.requested_at = Some(now);
+                true
+            },
+        }
+    }
+
+    /// The orderbook sync confidence of the `topic`, None if the orderbook has never been requested from relays.
+    fn sync_confidence(&self, topic: &str) -> Option<f64> {
+        self.sync_relays.get(topic).map(OrderbookSyncRelays::confidence)
+    }
+
+    /// Marks the requested orderbooks as not requested, so they are requested again on the next subscription.
+    /// Forgets the relays responded before as they have to confirm the orderbooks again.
+    /// Returns the topics of these orderbooks.
+    fn reset_requested_topics(&mut self, now: u64) -> Vec<String> {
+        self.sync_relays.clear();
+        let mut reset = Vec::new();
+        for (topic, state) in self.topics_subscribed_to.iter_mut() {
+            if *state == OrderbookRequestingState::Requested {
+                *state = OrderbookRequestingState::NotRequested { subscribed_at: now };
+                reset.push(topic.clone());
+            }
+        }
+        reset
+    }
+
+    /// Checks the maker orders that a taker request would be matched with, see [`MakerOrder::match_with_request`].
+    /// The `price` is in `rel` coin per 1 `base` coin and the `volume` is in `base` coin.
+    fn taker_liquidity(
+        &self,
+        base: &str,
+        rel: &str,
+        action: &TakerAction,
+        price: &MmNumber,
+        volume: &MmNumber,
+        match_by: &MatchBy,
+    ) -> TakerLiquidity {
+        let pair = match action {
+            TakerAction::Buy => (base.to_owned(), rel.to_owned()),
+            TakerAction::Sell => (rel.to_owned(), base.to_owned()),
+        };
+        let mut liquidity = TakerLiquidity {
+            matchable: false,
+            depth: MmNumber::from(0),
+        };
+        let uuids = match self.unordered.get(&pair) {
+            Some(uuids) => uuids,
+            None => return liquidity,
+        };
+
+        for order in uuids.iter().filter_map(|uuid| self.order_set.get(uuid)) {
+            let is_allowed = match match_by {
+                MatchBy::Any => true,
+                MatchBy::Orders(uuids) => uuids.contains(&order.uuid),
+                MatchBy::Pubkeys(pubkeys) => match hex::decode(&order.pubkey) {
+                    Ok(pubkey) if pubkey.len() == 33 => pubkeys.contains(&pubkey[1..].into()),
+                    _ => false,
+                },
+            };
+            if !is_allowed {
+                continue;
+            }
+
+            let maker_price = MmNumber::from(order.price.clone());
+            let max_volume = MmNumber::from(order.max_volume.clone());
+            let min_volume = MmNumber::from(order.min_volume.clone());
+            match action {
+                TakerAction::Buy => {
+                    if &maker_price > price {
+                        continue;
+                    }
+                    liquidity.matchable |= &min_volume <= volume && volume <= &max_volume;
+                    liquidity.depth += &max_volume;
+                },
+                // the maker sells `rel` for `base` at the `maker_price` in `base` coin per 1 `rel` coin
+                TakerAction::Sell => {
+                    if &maker_price * price > MmNumber::from(1) {
+                        continue;
+                    }
+                    let rel_volume = volume * price;
+                    liquidity.matchable |= min_volume <= rel_volume && rel_volume <= max_volume;
+                    liquidity.depth += &(&max_volume * &maker_price);
+                },
+            }
+        }
+        liquidity
+    }
+
+    /// The lowest price of the orders selling `base` for `rel`, in `rel` coin per 1 `base` coin.
+    fn best_ask_price(&self, base: &str, rel: &str) -> Option<MmNumber> {
+        let uuids = self.unordered.get(&(base.to_owned(), rel.to_owned()))?;
+        uuids
+            .iter()
+            .filter_map(|uuid| self.order_set.get(uuid))
+            .map(|order| MmNumber::from(order.price.clone()))
+            .min()
+    }
+
+    /// Whether the price of the maker reservation exceeds the best ask of the orderbook by more than `max_deviation`.
+    /// The `max_deviation` is the fraction of the best price, e.g. 0.05 allows the reservation to be 5% worse.
+    fn reserved_price_deviates(&self, reserved: &MakerReserved, max_deviation: &MmNumber) -> bool {
+        let zero = MmNumber::from(0);
+        if reserved.get_base_amount() <= &zero {
+            return false;
+        }
+        let best_price = match self.best_ask_price(&reserved.base, &reserved.rel) {
+            Some(price) => price,
+            None => return false,
+        };
+        let reserved_price = reserved.get_rel_amount() / reserved.get_base_amount();
+        let max_price = &best_price * &(MmNumber::from(1) + max_deviation.clone());
+        reserved_price > max_price
+    }
+
+    fn remove_expired_pending_updates(&mut self, now: u64) {
+        self.pending_updates
+            .retain(|_, pending| pending.received_at + PENDING_UPDATES_TTL > now);
+    }
+
     fn find_order_by_uuid_and_pubkey(&self, uuid: &Uuid, from_pubkey: &str) -> Option<OrderbookItem> {
         self.order_set.get(uuid).and_then(|order| {
             if order.pubkey == from_pubkey {
@@ -2031,6 +2741,45 @@ impl Orderbook {
                 next_root: *pair_root,
             });
         }
+
+        self.evict_pubkey_orders_over_limit(&order.pubkey);
+    }
+
+    /// Removes the oldest by `created_at` orders of the `pubkey` if it exceeds the `max_orders_per_pubkey` limit.
+    /// The orders are removed with the trie update, so our trie roots and diffs history stay consistent.
+    /// Our own orders are never evicted.
+    fn evict_pubkey_orders_over_limit(&mut self, pubkey: &str) {
+        let max_orders = match self.max_orders_per_pubkey {
+            Some(max) => max,
+            None => return,
+        };
+        if self.my_pubsecp.as_deref() == Some(pubkey) {
+            return;
+        }
+        let pubkey_uuids = match self.pubkeys_state.get(pubkey) {
+            Some(state) if state.orders_uuids.len() > max_orders => &state.orders_uuids,
+            _ => return,
+        };
+
+        let excess = pubkey_uuids.len() - max_orders;
+        let mut orders: Vec<_> = pubkey_uuids
+            .iter()
+            .filter_map(|(uuid, _)| self.order_set.get(uuid))
+            .map(|order| (order.created_at, order.uuid))
+            .collect();
+        orders.sort_unstable();
+        let to_evict = orders.into_iter().take(excess).map(|(_, uuid)| uuid);
+
+        for uuid in to_evict {
+            log::warn!(
+                "Pubkey {} exceeds the limit of {} orders, evicting order {}",
+                pubkey,
+                max_orders,
+                uuid
+            );
+            self.remove_order_trie_update(uuid);
+            self.evicted_orders += 1;
+        }
     }
 
     fn insert_or_update_order(&mut self, order: OrderbookItem) {
@@ -2066,7 +2815,11 @@ impl Orderbook {
             .or_insert_with(HashSet::new)
             .insert(order.uuid);
 
-        self.order_set.insert(order.uuid, order);
+        let event = match self.order_set.insert(order.uuid, order.clone()) {
+            Some(_) => OrderbookEvent::OrderUpdated(order),
+            None => OrderbookEvent::OrderInserted(order),
+        };
+        self.notify_event(event);
     }
 
     fn remove_order(&mut self, uuid: Uuid) -> Option<OrderbookItem> {
@@ -2096,6 +2849,7 @@ impl Orderbook {
                 self.unordered.remove(&base_rel);
             }
         };
+        self.notify_event(OrderbookEvent::OrderRemoved(order.clone()));
         Some(order)
     }
 
@@ -2126,6 +2880,7 @@ impl Orderbook {
                 self.unordered.remove(&base_rel);
             }
         }
+        self.notify_event(OrderbookEvent::OrderRemoved(order.clone()));
 
         let alb_ordered = alb_ordered_pair(&order.base, &order.rel);
         let pubkey_state = pubkey_state_mut(&mut self.pubkeys_state, &order.pubkey);
@@ -2216,6 +2971,28 @@ struct OrdermatchContext {
     pub orderbook: AsyncMutex<Orderbook>,
     pub order_requests_tracker: AsyncMutex<OrderRequestsTracker>,
     pub inactive_orders: AsyncMutex<HashMap<Uuid, OrderbookItem>>,
+    pub swap_outcomes: AsyncMutex<SwapOutcomesLog>,
+    pub orderbook_events_subscriptions: AsyncMutex<OrderbookEventsSubscriptions>,
+    pub pair_filter: AsyncMutex<PairFilter>,
+    /// Only the orders of these pubkeys are accepted if set, see the `maker_pubkeys_allowlist` config field
+    pub maker_pubkeys_allowlist: Option<HashSet<String>>,
+    /// Whether our keep-alive is going to be broadcasted by [`schedule_my_keep_alive`]
+    pub my_keep_alive_scheduled: AtomicBool,
+}
+
+/// Checks the config fields that [`OrdermatchContext::from_ctx`] falls back to the defaults on,
+/// so the node doesn't start with the misconfigured filters silently ignored.
+pub fn validate_ordermatch_conf(conf: &Json) -> Result<(), String> {
+    try_s!(PairFilter::from_conf(conf));
+    try_s!(maker_pubkeys_allowlist_from_conf(conf));
+    Ok(())
+}
+
+/// Parses the `maker_pubkeys_allowlist` config field.
+/// The hex encoded pubkeys are lowercased to be compared with the pubkeys of the orderbook.
+fn maker_pubkeys_allowlist_from_conf(conf: &Json) -> Result<Option<HashSet<String>>, String> {
+    let allowlist: Option<HashSet<String>> = try_s!(json::from_value(conf["maker_pubkeys_allowlist"].clone()));
+    Ok(allowlist.map(|allowlist| allowlist.iter().map(|pubkey| pubkey.to_lowercase()).collect()))
 }
 
 #[cfg_attr(test, mockable)]
@@ -2223,10 +3000,55 @@ impl OrdermatchContext {
     /// Obtains a reference to this crate context, creating it if necessary.
     fn from_ctx(ctx: &MmArc) -> Result<Arc<OrdermatchContext>, String> {
         Ok(try_s!(from_ctx(&ctx.ordermatch_ctx, move || {
-            Ok(OrdermatchContext::default())
+            let max_orders_per_pubkey = match ctx.conf["max_orders_per_pubkey"].as_u64() {
+                Some(0) => None,
+                Some(max) => Some(max as usize),
+                None => Some(MAX_ORDERS_PER_PUBKEY),
+            };
+            let min_sync_relays = ctx.conf["orderbook_min_relays"]
+                .as_u64()
+                .map(|min| min as usize)
+                .unwrap_or(ORDERBOOK_MIN_RELAYS);
+            let orderbook = Orderbook {
+                max_orders_per_pubkey,
+                my_pubsecp: ctx
+                    .secp256k1_key_pair
+                    .as_option()
+                    .map(|key_pair| hex::encode(&**key_pair.public())),
+                min_sync_relays,
+                ..Default::default()
+            };
+            let pair_filter = PairFilter::from_conf(&ctx.conf).unwrap_or_else(|e| {
+                log::error!(
+                    "Error {} on parsing the pair_filter config, the pairs are not filtered",
+                    e
+                );
+                PairFilter::default()
+            });
+            let maker_pubkeys_allowlist = maker_pubkeys_allowlist_from_conf(&ctx.conf).unwrap_or_else(|e| {
+                log::error!(
+                    "Error {} on parsing the maker_pubkeys_allowlist config, the pubkeys are not filtered",
+                    e
+                );
+                None
+            });
+            Ok(OrdermatchContext {
+                orderbook: AsyncMutex::new(orderbook),
+                pair_filter: AsyncMutex::new(pair_filter),
+                maker_pubkeys_allowlist,
+                ..Default::default()
+            })
         })))
     }
 
+    /// Whether the orders and keep-alives of the hex encoded `pubkey` are accepted by our node.
+    fn is_maker_pubkey_allowed(&self, pubkey: &str) -> bool {
+        match &self.maker_pubkeys_allowlist {
+            Some(allowlist) => allowlist.contains(pubkey),
+            None => true,
+        }
+    }
+
     /// Obtains a reference to this crate context, creating it if necessary.
     #[allow(dead_code)]
     fn from_ctx_weak(ctx_weak: &MmWeak) -> Result<Arc<OrdermatchContext>, String> {
@@ -2264,16 +3086,24 @@ fn lp_connect_start_bob(ctx: MmArc, maker_match: MakerMatch, maker_order: MakerO
         };
         let mut alice = bits256::default();
         alice.bytes = maker_match.request.sender_pubkey.0;
-        let maker_amount = maker_match.reserved.get_base_amount().to_decimal();
-        let taker_amount = maker_match.reserved.get_rel_amount().to_decimal();
-        let privkey = &ctx.secp256k1_key_pair().private().secret;
-        let my_persistent_pub = compressed_pub_key_from_priv_raw(&privkey[..], ChecksumType::DSHA256).unwrap();
         let uuid = maker_match.request.uuid;
+        let taker_coin_policy = match maker_confs_policy(&ctx, taker_coin.ticker()) {
+            Ok(policy) => policy,
+            Err(e) => {
+                log::error!(
+                    "Error {} on parsing the maker_confs_policy of {}",
+                    e,
+                    taker_coin.ticker()
+                );
+                None
+            },
+        };
         let my_conf_settings = choose_maker_confs_and_notas(
             maker_order.conf_settings,
             &maker_match.request,
             &maker_coin,
             &taker_coin,
+            taker_coin_policy,
         );
         // detect atomic lock time version implicitly by conf_settings existence in taker request
         let atomic_locktime_v = match maker_match.request.conf_settings {
@@ -2287,12 +3117,15 @@ fn lp_connect_start_bob(ctx: MmArc, maker_match: MakerMatch, maker_order: MakerO
             },
             None => AtomicLocktimeVersion::V1,
         };
+        let lock_time_version = atomic_locktime_v.saved_version();
         let lock_time = lp_atomic_locktime(maker_coin.ticker(), taker_coin.ticker(), atomic_locktime_v);
         log::info!(
-            "Entering the maker_swap_loop {}/{} with uuid: {}",
+            "Entering the maker_swap_loop {}/{} with uuid: {}, lock_time {} ({:?})",
             maker_coin.ticker(),
             taker_coin.ticker(),
-            uuid
+            uuid,
+            lock_time,
+            lock_time_version
         );
 
         let now = now_ms() / 1000;
@@ -2305,20 +3138,21 @@ fn lp_connect_start_bob(ctx: MmArc, maker_match: MakerMatch, maker_order: MakerO
         ) {
             error!("Error {} on new swap insertion", e);
         }
-        let maker_swap = MakerSwap::new(
-            ctx.clone(),
-            alice,
-            maker_amount,
-            taker_amount,
-            my_persistent_pub,
+        let matched_swap = MatchedSwap {
             uuid,
-            Some(maker_order.uuid),
-            my_conf_settings,
-            maker_coin,
-            taker_coin,
+            my_order_uuid: maker_order.uuid,
+            role: MatchedSwapRole::Maker,
+            maker_coin: maker_coin.ticker().to_owned(),
+            taker_coin: taker_coin.ticker().to_owned(),
+            maker_amount: maker_match.reserved.get_base_amount().clone(),
+            taker_amount: maker_match.reserved.get_rel_amount().clone(),
+            other_pubkey: alice,
             lock_time,
-        );
-        run_maker_swap(RunMakerSwapInput::StartNew(maker_swap), ctx).await;
+            lock_time_version,
+            conf_settings: my_conf_settings,
+        };
+        save_matched_swap(&ctx, &matched_swap);
+        run_matched_swap(ctx, matched_swap, maker_coin, taker_coin).await;
     });
 }
 
@@ -2351,10 +3185,6 @@ fn lp_connected_alice(ctx: MmArc, taker_request: TakerRequest, taker_match: Take
             },
         };
 
-        let privkey = &ctx.secp256k1_key_pair().private().secret;
-        let my_persistent_pub = compressed_pub_key_from_priv_raw(&privkey[..], ChecksumType::DSHA256).unwrap();
-        let maker_amount = taker_match.reserved.get_base_amount().clone();
-        let taker_amount = taker_match.reserved.get_rel_amount().clone();
         let uuid = taker_match.reserved.taker_order_uuid;
 
         let my_conf_settings =
@@ -2367,6 +3197,7 @@ fn lp_connected_alice(ctx: MmArc, taker_request: TakerRequest, taker_match: Take
                     &taker_request,
                     &maker_coin,
                     &taker_coin,
+                    None,
                 );
                 AtomicLocktimeVersion::V2 {
                     my_conf_settings,
@@ -2375,12 +3206,15 @@ fn lp_connected_alice(ctx: MmArc, taker_request: TakerRequest, taker_match: Take
             },
             None => AtomicLocktimeVersion::V1,
         };
+        let lock_time_version = atomic_locktime_v.saved_version();
         let locktime = lp_atomic_locktime(maker_coin.ticker(), taker_coin.ticker(), atomic_locktime_v);
         log::info!(
-            "Entering the taker_swap_loop {}/{} with uuid: {}",
+            "Entering the taker_swap_loop {}/{} with uuid: {}, lock_time {} ({:?})",
             maker_coin.ticker(),
             taker_coin.ticker(),
-            uuid
+            uuid,
+            locktime,
+            lock_time_version
         );
         let now = now_ms() / 1000;
         if let Err(e) = insert_new_swap_to_db(
@@ -2392,32 +3226,211 @@ fn lp_connected_alice(ctx: MmArc, taker_request: TakerRequest, taker_match: Take
         ) {
             error!("Error {} on new swap insertion", e);
         }
-        let taker_swap = TakerSwap::new(
-            ctx.clone(),
-            maker,
-            maker_amount,
-            taker_amount,
-            my_persistent_pub,
+        let matched_swap = MatchedSwap {
             uuid,
-            Some(uuid),
-            my_conf_settings,
-            maker_coin,
-            taker_coin,
-            locktime,
-        );
-        run_taker_swap(RunTakerSwapInput::StartNew(taker_swap), ctx).await
+            my_order_uuid: uuid,
+            role: MatchedSwapRole::Taker,
+            maker_coin: maker_coin.ticker().to_owned(),
+            taker_coin: taker_coin.ticker().to_owned(),
+            maker_amount: taker_match.reserved.get_base_amount().clone(),
+            taker_amount: taker_match.reserved.get_rel_amount().clone(),
+            other_pubkey: maker,
+            lock_time: locktime,
+            lock_time_version,
+            conf_settings: my_conf_settings,
+        };
+        save_matched_swap(&ctx, &matched_swap);
+        run_matched_swap(ctx, matched_swap, maker_coin, taker_coin).await
     });
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+enum MatchedSwapRole {
+    Maker,
+    Taker,
+}
+
+/// The parameters of the swap that are known at the moment of the orders matching.
+/// Saved before the swap is started to be able to start it again if the node is stopped
+/// before the swap saves its own state.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+struct MatchedSwap {
+    uuid: Uuid,
+    my_order_uuid: Uuid,
+    role: MatchedSwapRole,
+    maker_coin: String,
+    taker_coin: String,
+    maker_amount: MmNumber,
+    taker_amount: MmNumber,
+    other_pubkey: bits256,
+    lock_time: u64,
+    lock_time_version: SavedLocktimeVersion,
+    conf_settings: SwapConfirmationsSettings,
+}
+
+/// Runs the matched swap and removes the matched swap file once the swap is finished.
+async fn run_matched_swap(ctx: MmArc, matched_swap: MatchedSwap, maker_coin: MmCoinEnum, taker_coin: MmCoinEnum) {
+    let my_persistent_pub = {
+        let privkey = &ctx.secp256k1_key_pair().private().secret;
+        compressed_pub_key_from_priv_raw(&privkey[..], ChecksumType::DSHA256).unwrap()
+    };
+    let uuid = matched_swap.uuid;
+    match matched_swap.role {
+        MatchedSwapRole::Maker => {
+            let maker_swap = MakerSwap::new(
+                ctx.clone(),
+                matched_swap.other_pubkey,
+                matched_swap.maker_amount.to_decimal(),
+                matched_swap.taker_amount.to_decimal(),
+                my_persistent_pub,
+                uuid,
+                Some(matched_swap.my_order_uuid),
+                matched_swap.conf_settings,
+                maker_coin,
+                taker_coin,
+                matched_swap.lock_time,
+                Some(matched_swap.lock_time_version),
+            );
+            run_maker_swap(RunMakerSwapInput::StartNew(maker_swap), ctx.clone()).await;
+        },
+        MatchedSwapRole::Taker => {
+            let taker_swap = TakerSwap::new(
+                ctx.clone(),
+                matched_swap.other_pubkey,
+                matched_swap.maker_amount,
+                matched_swap.taker_amount,
+                my_persistent_pub,
+                uuid,
+                Some(matched_swap.my_order_uuid),
+                matched_swap.conf_settings,
+                maker_coin,
+                taker_coin,
+                matched_swap.lock_time,
+                Some(matched_swap.lock_time_version),
+            );
+            run_taker_swap(RunTakerSwapInput::StartNew(taker_swap), ctx.clone()).await;
+        },
+    }
+    delete_matched_swap(&ctx, &uuid);
+
+    let outcome = match load_my_swap(&ctx, &uuid) {
+        Ok(swap) => SwapOutcome::from_saved_swap(&swap),
+        Err(e) => {
+            log::error!("Error {} on swap {} loading", e, uuid);
+            return;
+        },
+    };
+    let my_pubkey = ctx.public_id().unwrap().to_string();
+    let other_pubkey = matched_swap.other_pubkey.to_string();
+    let (maker_pubkey, taker_pubkey) = match matched_swap.role {
+        MatchedSwapRole::Maker => (my_pubkey, other_pubkey),
+        MatchedSwapRole::Taker => (other_pubkey, my_pubkey),
+    };
+    record_swap_outcome(
+        &ctx,
+        uuid,
+        matched_swap.maker_coin,
+        matched_swap.taker_coin,
+        maker_pubkey,
+        taker_pubkey,
+        outcome,
+    )
+    .await;
+}
+
+/// Re-subscribes to the orderbook topics since the pubsub subscriptions could be lost while we were disconnected from the relays.
+/// Also requests the orderbooks that were requested before to resync them.
+///
+/// # Safety
+///
+/// The function locks [`MmCtx::p2p_ctx`] and [`MmCtx::ordermatch_ctx`]
+async fn resubscribe_to_orderbook_topics(ctx: &MmArc) {
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+    let (topics, to_request) = {
+        let mut orderbook = ordermatch_ctx.orderbook.lock().await;
+        let to_request = orderbook.reset_requested_topics(now_ms() / 1000);
+        let topics: Vec<_> = orderbook.topics_subscribed_to.keys().cloned().collect();
+        (topics, to_request)
+    };
+
+    for topic in topics {
+        subscribe_to_topic(ctx, topic).await;
+    }
+
+    for topic in to_request {
+        let ctx = ctx.clone();
+        spawn(async move {
+            if let Some((base, rel)) = parse_orderbook_pair_from_topic(&topic) {
+                if let Err(e) = request_and_fill_orderbook(&ctx, base, rel).await {
+                    log::error!("Error {} on requesting orderbook {}", e, topic);
+                }
+            }
+        });
+    }
+}
+
+/// Tracks the connectivity to the relays checked by [`lp_ordermatch_loop`].
+#[derive(Default)]
+struct RelaysConnectivity {
+    /// The timestamp of the last check in seconds
+    last_checked_at: u64,
+    /// The number of consecutive checks the relay mesh was empty on
+    empty_mesh_checks: u32,
+    disconnected: bool,
+}
+
+impl RelaysConnectivity {
+    fn check_due(&self, now: u64) -> bool { now >= self.last_checked_at + RELAYS_CONNECTIVITY_CHECK_INTERVAL }
+
+    /// Records the number of the connected relays.
+    /// Returns true if our node has reconnected after it was considered disconnected.
+    fn on_checked(&mut self, now: u64, relays_num: usize) -> bool {
+        self.last_checked_at = now;
+        if relays_num > 0 {
+            self.empty_mesh_checks = 0;
+            return std::mem::replace(&mut self.disconnected, false);
+        }
+
+        self.empty_mesh_checks += 1;
+        if !self.disconnected && self.empty_mesh_checks >= RELAYS_DISCONNECTED_CHECKS {
+            log::warn!(
+                "The relay mesh is empty on {} consecutive checks",
+                self.empty_mesh_checks
+            );
+            self.disconnected = true;
+        }
+        false
+    }
+}
+
 pub async fn lp_ordermatch_loop(ctx: MmArc) {
     let my_pubsecp = hex::encode(&**ctx.secp256k1_key_pair().public());
     let maker_order_timeout = ctx.conf["maker_order_timeout"].as_u64().unwrap_or(MAKER_ORDER_TIMEOUT);
+    let mut relays_connectivity = RelaysConnectivity::default();
     loop {
         if ctx.is_stopping() {
             break;
         }
         let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+
+        let now = now_ms() / 1000;
+        if relays_connectivity.check_due(now) {
+            match connected_relays(&ctx).await {
+                Ok(relays) => {
+                    if relays_connectivity.on_checked(now, relays.len()) {
+                        log::info!("Reconnected to the relays, resubscribing to the orderbook topics");
+                        resubscribe_to_orderbook_topics(&ctx).await;
+                    }
+                },
+                Err(e) => {
+                    // the next attempt is made on the next check only
+                    relays_connectivity.last_checked_at = now;
+                    log::error!("Error {} on getting the connected relays", e);
+                },
+            }
+        }
         {
+            let pair_filter = ordermatch_ctx.pair_filter.lock().await.clone();
             let mut my_taker_orders = ordermatch_ctx.my_taker_orders.lock().await;
             let mut my_maker_orders = ordermatch_ctx.my_maker_orders.lock().await;
             let _my_cancelled_orders = ordermatch_ctx.my_cancelled_orders.lock().await;
@@ -2426,7 +3439,9 @@ pub async fn lp_ordermatch_loop(ctx: MmArc) {
                 .drain()
                 .filter_map(|(uuid, order)| {
                     if order.created_at + order.timeout * 1000 < now_ms() {
-                        if order.matches.is_empty() && order.order_type == OrderType::GoodTillCancelled {
+                        let (base, rel) = order.request.maker_pair();
+                        let to_maker = order.matches.is_empty() && order.order_type == OrderType::GoodTillCancelled;
+                        if to_maker && pair_filter.is_allowed(base, rel) {
                             delete_my_taker_order(&ctx, &order, TakerOrderCancellationReason::ToMaker);
                             let maker_order: MakerOrder = order.into();
                             my_maker_orders.insert(uuid, maker_order.clone());
@@ -2441,6 +3456,14 @@ pub async fn lp_ordermatch_loop(ctx: MmArc) {
                                 }
                             });
                         } else {
+                            if to_maker {
+                                log::info!(
+                                    "The order {} of the denied pair {}/{} is not converted to maker",
+                                    uuid,
+                                    base,
+                                    rel
+                                );
+                            }
                             delete_my_taker_order(&ctx, &order, TakerOrderCancellationReason::TimedOut);
                         }
                         None
@@ -2464,10 +3487,18 @@ pub async fn lp_ordermatch_loop(ctx: MmArc) {
                     let ctx = ctx.clone();
                     async move {
                         if order.available_amount() < order.min_base_vol && !order.has_ongoing_matches() {
-                            if order.matches.is_empty() {
-                                delete_my_maker_order(&ctx, &order, MakerOrderCancellationReason::InsufficientBalance);
-                            } else {
-                                delete_my_maker_order(&ctx, &order, MakerOrderCancellationReason::Fulfilled);
+                            match order.filled() {
+                                Some(filled) => {
+                                    maker_order_filled_notify(&ctx, &filled);
+                                    delete_my_maker_order(&ctx, &order, MakerOrderCancellationReason::Fulfilled);
+                                },
+                                None => {
+                                    delete_my_maker_order(
+                                        &ctx,
+                                        &order,
+                                        MakerOrderCancellationReason::InsufficientBalance,
+                                    );
+                                },
                             }
                             maker_order_cancelled_p2p_notify(ctx.clone(), &order).await;
                             None
@@ -2542,11 +3573,42 @@ async fn process_maker_reserved(ctx: MmArc, from_pubkey: H256Json, reserved_msg:
         return;
     }
 
+    match json::from_value::<Option<MmNumber>>(ctx.conf["max_reserved_price_deviation"].clone()) {
+        Ok(Some(max_deviation)) => {
+            let orderbook = ordermatch_ctx.orderbook.lock().await;
+            if orderbook.reserved_price_deviates(&reserved_msg, &max_deviation) {
+                log::warn!(
+                    "Skip maker reserved {} for taker order {}: the price deviates from the best {}/{} price by more than {}, possible manipulation",
+                    reserved_msg.maker_order_uuid,
+                    reserved_msg.taker_order_uuid,
+                    reserved_msg.base,
+                    reserved_msg.rel,
+                    max_deviation
+                );
+                return;
+            }
+        },
+        Ok(None) => (),
+        Err(e) => log::error!("Error {} on parsing the max_reserved_price_deviation config", e),
+    }
+
+    let pair_filter = ordermatch_ctx.pair_filter.lock().await.clone();
     let mut my_taker_orders = ordermatch_ctx.my_taker_orders.lock().await;
     let my_order = match my_taker_orders.entry(reserved_msg.taker_order_uuid) {
         Entry::Vacant(_) => return,
         Entry::Occupied(entry) => entry.into_mut(),
     };
+    let (base, rel) = my_order.request.maker_pair();
+    if !pair_filter.is_allowed(base, rel) {
+        log::info!(
+            "Declined maker reserved {} for taker order {} of the denied pair {}/{}",
+            reserved_msg.maker_order_uuid,
+            reserved_msg.taker_order_uuid,
+            base,
+            rel
+        );
+        return;
+    }
 
     // send "connect" message if reserved message targets our pubkey AND
     // reserved amounts match our order AND order is NOT reserved by someone else (empty matches)
@@ -2620,13 +3682,33 @@ async fn process_taker_request(ctx: MmArc, from_pubkey: H256Json, taker_request:
     }
 
     let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+    let pair_filter = ordermatch_ctx.pair_filter.lock().await.clone();
     let mut my_orders = ordermatch_ctx.my_maker_orders.lock().await;
+    let swaps_limit_reached = concurrent_swaps_limit_reached(&ctx, &my_orders);
     let filtered = my_orders
         .iter_mut()
         .filter(|(uuid, _)| taker_request.can_match_with_uuid(uuid));
 
     for (uuid, order) in filtered {
+        if !pair_filter.is_allowed(&order.base, &order.rel) {
+            log::info!(
+                "Declined the request {} to the order {} of the denied pair {}/{}",
+                taker_request.uuid,
+                uuid,
+                order.base,
+                order.rel
+            );
+            continue;
+        }
         if let OrderMatchResult::Matched((base_amount, rel_amount)) = order.match_with_request(&taker_request) {
+            if swaps_limit_reached && !order.matches.contains_key(&taker_request.uuid) {
+                log::info!(
+                    "Declined the request {} to the order {}, the limit of concurrent swaps is reached",
+                    taker_request.uuid,
+                    uuid
+                );
+                return;
+            }
             let base_coin = match lp_coinfind(&ctx, &order.base).await {
                 Ok(Some(c)) => c,
                 _ => return, // attempt to match with deactivated coin
@@ -2636,6 +3718,28 @@ async fn process_taker_request(ctx: MmArc, from_pubkey: H256Json, taker_request:
                 _ => return, // attempt to match with deactivated coin
             };
 
+            if let Some(conf_settings) = &taker_request.conf_settings {
+                let max_confs = ctx.conf["max_taker_request_confs"]
+                    .as_u64()
+                    .unwrap_or(MAX_TAKER_REQUEST_CONFS);
+                // the taker request `base` is the maker order `base` on buy and `rel` on sell
+                let (request_base_coin, request_rel_coin) = match taker_request.action {
+                    TakerAction::Buy => (&base_coin, &rel_coin),
+                    TakerAction::Sell => (&rel_coin, &base_coin),
+                };
+                if let Err(e) =
+                    validate_taker_conf_settings(conf_settings, request_base_coin, request_rel_coin, max_confs)
+                {
+                    log::warn!(
+                        "Declined the request {} to the order {}: {}",
+                        taker_request.uuid,
+                        uuid,
+                        e
+                    );
+                    return;
+                }
+            }
+
             if !order.matches.contains_key(&taker_request.uuid) {
                 let reserved = MakerReserved {
                     dest_pub_key: taker_request.sender_pubkey.clone(),
@@ -2673,6 +3777,46 @@ async fn process_taker_request(ctx: MmArc, from_pubkey: H256Json, taker_request:
     }
 }
 
+/// Checks whether our node can't reserve one more match without exceeding the `max_concurrent_swaps` conf.
+/// The reserved but not connected yet matches are counted along with the running swaps as each of them can start a swap.
+fn concurrent_swaps_limit_reached(ctx: &MmArc, my_maker_orders: &HashMap<Uuid, MakerOrder>) -> bool {
+    let max_concurrent_swaps = match ctx.conf["max_concurrent_swaps"].as_u64() {
+        Some(max) => max,
+        None => return false,
+    };
+    let ongoing_matches = my_maker_orders
+        .values()
+        .flat_map(|order| order.matches.values())
+        .filter(|order_match| order_match.connect.is_none() && order_match.connected.is_none())
+        .count() as u64;
+    running_swaps_num(ctx) + ongoing_matches >= max_concurrent_swaps
+}
+
+/// Checks the confirmations that the taker request sets for its `base` and `rel` coins.
+/// Zero confirmations allow racing a reorg, so they are accepted only if the coin itself doesn't require confirmations.
+/// Too many confirmations would lock the maker funds for an unreasonably long time.
+fn validate_taker_conf_settings(
+    conf_settings: &OrderConfirmationsSettings,
+    base_coin: &MmCoinEnum,
+    rel_coin: &MmCoinEnum,
+    max_confs: u64,
+) -> Result<(), String> {
+    for (coin, confs) in [(base_coin, conf_settings.base_confs), (rel_coin, conf_settings.rel_confs)].iter() {
+        if *confs == 0 && coin.required_confirmations() > 0 {
+            return ERR!("Zero confirmations of {} are requested", coin.ticker());
+        }
+        if *confs > max_confs {
+            return ERR!(
+                "{} confirmations of {} are requested, the max is {}",
+                confs,
+                coin.ticker(),
+                max_confs
+            );
+        }
+    }
+    Ok(())
+}
+
 async fn process_taker_connect(ctx: MmArc, sender_pubkey: H256Json, connect_msg: TakerConnect) {
     let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
     let our_public_id = ctx.public_id().unwrap();
@@ -2700,6 +3844,15 @@ async fn process_taker_connect(ctx: MmArc, sender_pubkey: H256Json, connect_msg:
         log::warn!("Connect message sender pubkey != request message sender pubkey");
         return;
     }
+    // the swap must not be started twice even if the match was reset after the swap had been started
+    if my_order.started_swaps.contains(&connect_msg.taker_order_uuid) {
+        log::warn!(
+            "Ignoring the replayed connect of the taker order {} to our maker order {}, the swap is already started",
+            connect_msg.taker_order_uuid,
+            connect_msg.maker_order_uuid
+        );
+        return;
+    }
 
     if order_match.connected.is_none() && order_match.connect.is_none() {
         let connected = MakerConnected {
@@ -2750,10 +3903,15 @@ pub struct AutoBuyInput {
     rel_confs: Option<u64>,
     rel_nota: Option<bool>,
     min_volume: Option<MmNumber>,
+    /// Fail if there is no maker order in our orderbook that can fill the request
+    #[serde(default)]
+    check_liquidity: bool,
 }
 
 pub async fn buy(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, String> {
-    let input: AutoBuyInput = try_s!(json::from_value(req));
+    let mut input: AutoBuyInput = try_s!(json::from_value(req));
+    input.base = canonical_ticker(&ctx, &input.base);
+    input.rel = canonical_ticker(&ctx, &input.rel);
     if input.base == input.rel {
         return ERR!("Base and rel must be different coins");
     }
@@ -2785,7 +3943,9 @@ pub async fn buy(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, String> {
 }
 
 pub async fn sell(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, String> {
-    let input: AutoBuyInput = try_s!(json::from_value(req));
+    let mut input: AutoBuyInput = try_s!(json::from_value(req));
+    input.base = canonical_ticker(&ctx, &input.base);
+    input.rel = canonical_ticker(&ctx, &input.rel);
     if input.base == input.rel {
         return ERR!("Base and rel must be different coins");
     }
@@ -2897,9 +4057,44 @@ pub async fn lp_auto_buy(
         Some("sell") => TakerAction::Sell,
         _ => return ERR!("Auto buy must be called only from buy/sell RPC methods"),
     };
-    let request_orderbook = false;
-    try_s!(subscribe_to_orderbook_topic(&ctx, &input.base, &input.rel, request_orderbook).await);
     let ordermatch_ctx = try_s!(OrdermatchContext::from_ctx(&ctx));
+    // filter the order by the pair of the maker order it would be converted to, the same as `TakerRequest::maker_pair`
+    let (sell_coin, buy_coin) = match action {
+        TakerAction::Sell => (&input.base, &input.rel),
+        TakerAction::Buy => (&input.rel, &input.base),
+    };
+    if !ordermatch_ctx.pair_filter.lock().await.is_allowed(sell_coin, buy_coin) {
+        log::info!(
+            "Refused to create the taker order of the denied pair {}/{}",
+            sell_coin,
+            buy_coin
+        );
+        return ERR!("The pair {}/{} is denied by the pair filter", sell_coin, buy_coin);
+    }
+    // the orderbook is needed to check the liquidity only
+    let request_orderbook = input.check_liquidity;
+    try_s!(subscribe_to_orderbook_topic(&ctx, &input.base, &input.rel, request_orderbook).await);
+    if input.check_liquidity {
+        let liquidity = ordermatch_ctx.orderbook.lock().await.taker_liquidity(
+            &input.base,
+            &input.rel,
+            &action,
+            &input.price,
+            &input.volume,
+            &input.match_by,
+        );
+        if !liquidity.matchable {
+            return ERR!(
+                "There is no maker order to {} {} {} at the price {} or better, the available depth is {} {}",
+                input.method,
+                input.volume,
+                input.base,
+                input.price,
+                liquidity.depth,
+                input.base
+            );
+        }
+    }
     let mut my_taker_orders = ordermatch_ctx.my_taker_orders.lock().await;
     let our_public_id = try_s!(ctx.public_id());
     let rel_volume = &input.volume * &input.price;
@@ -2981,6 +4176,22 @@ impl Hasher for Blake2Hasher64 {
 type Layout = sp_trie::Layout<Blake2Hasher64>;
 
 impl OrderbookItem {
+    /// The inverse of the `From<(new_protocol::MakerOrderCreated, String)>` conversion.
+    fn to_maker_order_created(&self, conf_settings: OrderConfirmationsSettings) -> new_protocol::MakerOrderCreated {
+        new_protocol::MakerOrderCreated {
+            uuid: self.uuid.into(),
+            base: self.base.clone(),
+            rel: self.rel.clone(),
+            price: self.price.clone(),
+            max_volume: self.max_volume.clone(),
+            min_volume: self.min_volume.clone(),
+            conf_settings,
+            created_at: self.created_at,
+            timestamp: now_ms() / 1000,
+            pair_trie_root: H64::default(),
+        }
+    }
+
     fn apply_updated(&mut self, msg: &new_protocol::MakerOrderUpdated) {
         if let Some(new_price) = msg.new_price() {
             self.price = new_price.into();
@@ -3291,7 +4502,19 @@ async fn get_max_volume(ctx: &MmArc, my_coin: &MmCoinEnum, other_coin: &MmCoinEn
 }
 
 pub async fn set_price(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, String> {
-    let req: SetPriceReq = try_s!(json::from_value(req));
+    let mut req: SetPriceReq = try_s!(json::from_value(req));
+    req.base = canonical_ticker(&ctx, &req.base);
+    req.rel = canonical_ticker(&ctx, &req.rel);
+
+    let ordermatch_ctx = try_s!(OrdermatchContext::from_ctx(&ctx));
+    if !ordermatch_ctx.pair_filter.lock().await.is_allowed(&req.base, &req.rel) {
+        log::info!(
+            "Refused to create the maker order of the denied pair {}/{}",
+            req.base,
+            req.rel
+        );
+        return ERR!("The pair {}/{} is denied by the pair filter", req.base, req.rel);
+    }
 
     let base_coin: MmCoinEnum = match try_s!(lp_coinfind(&ctx, &req.base).await) {
         Some(coin) => coin,
@@ -3333,7 +4556,6 @@ pub async fn set_price(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, Strin
         req.volume.clone()
     };
 
-    let ordermatch_ctx = try_s!(OrdermatchContext::from_ctx(&ctx));
     let mut my_orders = ordermatch_ctx.my_maker_orders.lock().await;
 
     if req.cancel_previous {
@@ -3387,6 +4609,7 @@ pub async fn update_maker_order(ctx: MmArc, req: Json) -> Result<Response<Vec<u8
     let req: MakerOrderUpdateReq = try_s!(json::from_value(req));
 
     let ordermatch_ctx = try_s!(OrdermatchContext::from_ctx(&ctx));
+    let pair_filter = ordermatch_ctx.pair_filter.lock().await.clone();
     let my_maker_orders = ordermatch_ctx.my_maker_orders.lock().await;
 
     let (base_coin, rel_coin, original_price, original_volume, updated_conf_settings, matches, reserved_amount) =
@@ -3395,6 +4618,15 @@ pub async fn update_maker_order(ctx: MmArc, req: Json) -> Result<Response<Vec<u8
                 if order.has_ongoing_matches() {
                     return ERR!("Can't update an order that has ongoing matches");
                 }
+                if !pair_filter.is_allowed(&order.base, &order.rel) {
+                    log::info!(
+                        "Refused to update the maker order {} of the denied pair {}/{}",
+                        req.uuid,
+                        order.base,
+                        order.rel
+                    );
+                    return ERR!("The pair {}/{} is denied by the pair filter", order.base, order.rel);
+                }
                 let base = order.base.as_str();
                 let base_coin: MmCoinEnum = match try_s!(lp_coinfind(&ctx, base).await) {
                     Some(coin) => coin,
@@ -3912,6 +5144,8 @@ fn my_taker_orders_dir(ctx: &MmArc) -> PathBuf { ctx.dbdir().join("ORDERS").join
 
 fn my_orders_history_dir(ctx: &MmArc) -> PathBuf { ctx.dbdir().join("ORDERS").join("MY").join("HISTORY") }
 
+fn my_matched_swaps_dir(ctx: &MmArc) -> PathBuf { ctx.dbdir().join("ORDERS").join("MY").join("MATCHED") }
+
 pub fn my_maker_order_file_path(ctx: &MmArc, uuid: &Uuid) -> PathBuf {
     my_maker_orders_dir(ctx).join(format!("{}.json", uuid))
 }
@@ -3924,6 +5158,10 @@ fn my_order_history_file_path(ctx: &MmArc, uuid: &Uuid) -> PathBuf {
     my_orders_history_dir(ctx).join(format!("{}.json", uuid))
 }
 
+fn my_matched_swap_file_path(ctx: &MmArc, uuid: &Uuid) -> PathBuf {
+    my_matched_swaps_dir(ctx).join(format!("{}.json", uuid))
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 struct HistoricalOrder {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -3970,6 +5208,7 @@ impl HistoricalOrder {
     }
 }
 
+#[cfg_attr(test, mockable)]
 fn save_my_maker_order(ctx: &MmArc, order: &MakerOrder) {
     let path = my_maker_order_file_path(ctx, &order.uuid);
     let content = json::to_vec(order).unwrap();
@@ -3992,6 +5231,7 @@ fn save_maker_order_on_update(ctx: &MmArc, order: &MakerOrder) {
     }
 }
 
+#[cfg_attr(test, mockable)]
 fn save_my_taker_order(ctx: &MmArc, order: &TakerOrder) {
     let path = my_taker_order_file_path(ctx, &order.request.uuid);
     let content = json::to_vec(order).unwrap();
@@ -4014,6 +5254,20 @@ fn save_my_order_in_history(ctx: &MmArc, order: &Order) {
     write(&path, &content).unwrap();
 }
 
+fn save_matched_swap(ctx: &MmArc, matched_swap: &MatchedSwap) {
+    let path = my_matched_swap_file_path(ctx, &matched_swap.uuid);
+    let content = json::to_vec(matched_swap).unwrap();
+    write(&path, &content).unwrap();
+}
+
+fn delete_matched_swap(ctx: &MmArc, uuid: &Uuid) {
+    let path = my_matched_swap_file_path(ctx, uuid);
+    match remove_file(&path) {
+        Ok(_) => (),
+        Err(e) => log::warn!("Could not remove matched swap file {}, error {}", path.display(), e),
+    }
+}
+
 #[cfg_attr(test, mockable)]
 fn delete_my_maker_order(ctx: &MmArc, order: &MakerOrder, reason: MakerOrderCancellationReason) {
     let path = my_maker_order_file_path(ctx, &order.uuid);
@@ -4048,11 +5302,21 @@ fn delete_my_taker_order(ctx: &MmArc, order: &TakerOrder, reason: TakerOrderCanc
 pub async fn orders_kick_start(ctx: &MmArc) -> Result<HashSet<String>, String> {
     let mut coins = HashSet::new();
     let ordermatch_ctx = try_s!(OrdermatchContext::from_ctx(ctx));
+    let pair_filter = ordermatch_ctx.pair_filter.lock().await.clone();
     let mut maker_orders = ordermatch_ctx.my_maker_orders.lock().await;
     let maker_entries = try_s!(json_dir_entries(&my_maker_orders_dir(&ctx)));
 
     maker_entries.iter().for_each(|entry| {
         if let Ok(order) = json::from_slice::<MakerOrder>(&slurp(&entry.path())) {
+            // the order is kept as the filter can be changed, it's just not matched while the pair is denied
+            if !pair_filter.is_allowed(&order.base, &order.rel) {
+                log::info!(
+                    "The saved maker order {} of the denied pair {}/{} is not matched",
+                    order.uuid,
+                    order.base,
+                    order.rel
+                );
+            }
             coins.insert(order.base.clone());
             coins.insert(order.rel.clone());
             maker_orders.insert(order.uuid, order);
@@ -4064,6 +5328,15 @@ pub async fn orders_kick_start(ctx: &MmArc) -> Result<HashSet<String>, String> {
 
     taker_entries.iter().for_each(|entry| {
         if let Ok(order) = json::from_slice::<TakerOrder>(&slurp(&entry.path())) {
+            let (base, rel) = order.request.maker_pair();
+            if !pair_filter.is_allowed(base, rel) {
+                log::info!(
+                    "The saved taker order {} of the denied pair {}/{} is not matched",
+                    order.request.uuid,
+                    base,
+                    rel
+                );
+            }
             coins.insert(order.request.base.clone());
             coins.insert(order.request.rel.clone());
             taker_orders.insert(order.request.uuid, order);
@@ -4072,6 +5345,65 @@ pub async fn orders_kick_start(ctx: &MmArc) -> Result<HashSet<String>, String> {
     Ok(coins)
 }
 
+/// Starts the matched swaps again if the node was stopped before they saved their own state.
+/// The swaps that have the swap file are kick started by `swap_kick_starts` so their matched swap files are removed.
+pub async fn matched_swaps_kick_start(ctx: &MmArc) -> Result<HashSet<String>, String> {
+    let mut coins = HashSet::new();
+    let entries = try_s!(json_dir_entries(&my_matched_swaps_dir(&ctx)));
+
+    for entry in entries {
+        let matched_swap = match json::from_slice::<MatchedSwap>(&slurp(&entry.path())) {
+            Ok(s) => s,
+            Err(e) => {
+                log::warn!("Error {} on matched swap {} deserialization", e, entry.path().display());
+                continue;
+            },
+        };
+        if my_swap_file_path(ctx, &matched_swap.uuid).exists() {
+            delete_matched_swap(ctx, &matched_swap.uuid);
+            continue;
+        }
+
+        log::info!("Kick starting the matched swap {}", matched_swap.uuid);
+        coins.insert(matched_swap.maker_coin.clone());
+        coins.insert(matched_swap.taker_coin.clone());
+        let ctx = ctx.clone();
+        spawn(async move {
+            let uuid = matched_swap.uuid;
+            let maker_coin = match find_coin_for_matched_swap(&ctx, &matched_swap.maker_coin, &uuid).await {
+                Some(c) => c,
+                None => return,
+            };
+            let taker_coin = match find_coin_for_matched_swap(&ctx, &matched_swap.taker_coin, &uuid).await {
+                Some(c) => c,
+                None => return,
+            };
+            run_matched_swap(ctx, matched_swap, maker_coin, taker_coin).await;
+        });
+    }
+    Ok(coins)
+}
+
+async fn find_coin_for_matched_swap(ctx: &MmArc, ticker: &str, uuid: &Uuid) -> Option<MmCoinEnum> {
+    loop {
+        match lp_coinfind(ctx, ticker).await {
+            Ok(Some(c)) => return Some(c),
+            Ok(None) => {
+                log::info!(
+                    "Can't kickstart the matched swap {} until the coin {} is activated",
+                    uuid,
+                    ticker
+                );
+                Timer::sleep(5.).await;
+            },
+            Err(e) => {
+                log::error!("Error {} on {} find attempt", e, ticker);
+                return None;
+            },
+        };
+    }
+}
+
 #[derive(Deserialize)]
 #[serde(tag = "type", content = "data")]
 pub enum CancelBy {
@@ -4173,6 +5505,16 @@ pub async fn cancel_orders_by(ctx: &MmArc, cancel_by: CancelBy) -> Result<(Vec<U
 
 pub async fn cancel_all_orders(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, String> {
     let cancel_by: CancelBy = try_s!(json::from_value(req["cancel_by"].clone()));
+    let cancel_by = match cancel_by {
+        CancelBy::Pair { base, rel } => CancelBy::Pair {
+            base: canonical_ticker(&ctx, &base),
+            rel: canonical_ticker(&ctx, &rel),
+        },
+        CancelBy::Coin { ticker } => CancelBy::Coin {
+            ticker: canonical_ticker(&ctx, &ticker),
+        },
+        CancelBy::All => CancelBy::All,
+    };
 
     let (cancelled, currently_matching) = try_s!(cancel_orders_by(&ctx, cancel_by).await);
 
@@ -4205,8 +5547,9 @@ pub(self) async fn subscribe_to_orderbook_topic(
     let is_orderbook_filled = {
         let ordermatch_ctx = try_s!(OrdermatchContext::from_ctx(ctx));
         let mut orderbook = ordermatch_ctx.orderbook.lock().await;
+        let min_sync_relays = orderbook.min_sync_relays;
 
-        match orderbook.topics_subscribed_to.entry(topic.clone()) {
+        let is_orderbook_filled = match orderbook.topics_subscribed_to.entry(topic.clone()) {
             Entry::Vacant(e) => {
                 // we weren't subscribed to the topic yet
                 e.insert(OrderbookRequestingState::NotRequested {
@@ -4222,10 +5565,11 @@ pub(self) async fn subscribe_to_orderbook_topic(
                     true
                 },
                 OrderbookRequestingState::NotRequested { subscribed_at }
-                    if *subscribed_at + ORDERBOOK_REQUESTING_TIMEOUT < current_timestamp =>
+                    if min_sync_relays <= 1 && *subscribed_at + ORDERBOOK_REQUESTING_TIMEOUT < current_timestamp =>
                 {
                     // We are subscribed to the topic. Also we didn't request the orderbook,
                     // but enough time has passed for the orderbook to fill by OrdermatchRequest::SyncPubkeyOrderbookState.
+                    // It's not the case if several relays must confirm the orderbook.
                     true
                 }
                 OrderbookRequestingState::NotRequested { .. } => {
@@ -4234,7 +5578,17 @@ pub(self) async fn subscribe_to_orderbook_topic(
                     false
                 },
             },
+        };
+
+        // all relays are requested if several of them must respond, don't flood them with the requests of every RPC call
+        if !is_orderbook_filled
+            && request_orderbook
+            && min_sync_relays > 1
+            && !orderbook.try_start_sync_request(&topic, current_timestamp)
+        {
+            return Ok(());
         }
+        is_orderbook_filled
     };
 
     if !is_orderbook_filled && request_orderbook {
@@ -4278,11 +5632,30 @@ pub struct RpcOrderbookEntry {
     rel_min_volume: DetailedRelMinVolume,
 }
 
+/// The minimum confirmations and notarization of the taker payment that our node requires as a maker
+/// regardless of the maker order and taker request settings.
+/// Set per taker coin by the `maker_confs_policy` config, e.g. `{"maker_confs_policy": {"RICK": {"min_confs": 2, "requires_nota": false}}}`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+struct MakerConfsPolicy {
+    #[serde(default)]
+    min_confs: u64,
+    #[serde(default)]
+    requires_nota: bool,
+}
+
+fn maker_confs_policy(ctx: &MmArc, taker_coin: &str) -> Result<Option<MakerConfsPolicy>, String> {
+    Ok(try_s!(json::from_value(
+        ctx.conf["maker_confs_policy"][taker_coin].clone()
+    )))
+}
+
+/// The `taker_coin_policy` is applied by maker only, so it must be None when taker chooses the maker settings.
 fn choose_maker_confs_and_notas(
     maker_confs: Option<OrderConfirmationsSettings>,
     taker_req: &TakerRequest,
     maker_coin: &MmCoinEnum,
     taker_coin: &MmCoinEnum,
+    taker_coin_policy: Option<MakerConfsPolicy>,
 ) -> SwapConfirmationsSettings {
     let maker_settings = maker_confs.unwrap_or(OrderConfirmationsSettings {
         base_confs: maker_coin.required_confirmations(),
@@ -4291,7 +5664,7 @@ fn choose_maker_confs_and_notas(
         rel_nota: taker_coin.requires_notarization(),
     });
 
-    let (maker_coin_confs, maker_coin_nota, taker_coin_confs, taker_coin_nota) = match taker_req.conf_settings {
+    let (maker_coin_confs, maker_coin_nota, mut taker_coin_confs, mut taker_coin_nota) = match taker_req.conf_settings {
         Some(taker_settings) => match taker_req.action {
             TakerAction::Sell => {
                 let maker_coin_confs = if taker_settings.rel_confs < maker_settings.base_confs {
@@ -4338,6 +5711,24 @@ fn choose_maker_confs_and_notas(
         ),
     };
 
+    if let Some(policy) = taker_coin_policy {
+        if taker_coin_confs < policy.min_confs {
+            log::info!(
+                "Maker policy overrides {} confirmations {} with {}",
+                taker_coin.ticker(),
+                taker_coin_confs,
+                policy.min_confs
+            );
+            taker_coin_confs = policy.min_confs;
+        }
+        // the notarization affects the V2 lock time that is calculated by taker from the maker reserved settings,
+        // so it can be overridden only if the taker omitted the settings and the V1 lock time is used
+        if policy.requires_nota && !taker_coin_nota && taker_req.conf_settings.is_none() {
+            log::info!("Maker policy requires {} notarization", taker_coin.ticker());
+            taker_coin_nota = true;
+        }
+    }
+
     SwapConfirmationsSettings {
         maker_coin_confs,
         maker_coin_nota,
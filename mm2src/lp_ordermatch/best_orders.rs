@@ -1,4 +1,4 @@
-use super::{OrderbookItemWithProof, OrdermatchContext, OrdermatchRequest};
+use super::{canonical_ticker, OrderbookItemWithProof, OrdermatchContext, OrdermatchRequest};
 use crate::mm2::lp_network::{request_any_relay, P2PRequest};
 use coins::{address_by_coin_conf_and_pubkey_str, coin_conf, is_wallet_only_conf, is_wallet_only_ticker};
 use common::log;
@@ -105,7 +105,8 @@ pub async fn process_best_orders_p2p_request(
 }
 
 pub async fn best_orders_rpc(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, String> {
-    let req: BestOrdersRequest = try_s!(json::from_value(req));
+    let mut req: BestOrdersRequest = try_s!(json::from_value(req));
+    req.coin = canonical_ticker(&ctx, &req.coin);
     if is_wallet_only_ticker(&ctx, &req.coin) {
         return ERR!("Coin {} is wallet only", &req.coin);
     }
@@ -0,0 +1,196 @@
+use super::{MakerOrder, Orderbook, OrdermatchContext, PairFilter, MAKER_ORDER_TIMEOUT};
+use crate::mm2::lp_swap::is_pubkey_banned;
+use coins::lp_coinfind;
+use common::{mm_ctx::MmArc, now_ms};
+use http::Response;
+use serde_json::{self as json, Value as Json};
+use std::collections::BTreeMap;
+
+/// The liquidity of the alphabetically ordered `base`/`rel` pair.
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct PairLiquidity {
+    base: String,
+    rel: String,
+    /// The number of the orders selling `base` for `rel`
+    asks: usize,
+    /// The number of the orders selling `rel` for `base`
+    bids: usize,
+    /// The number of our maker orders on the pair in both directions
+    my_orders: usize,
+}
+
+fn pair_entry<'a>(
+    pairs: &'a mut BTreeMap<(String, String), PairLiquidity>,
+    base: &str,
+    rel: &str,
+) -> &'a mut PairLiquidity {
+    let (first, second) = if base < rel { (base, rel) } else { (rel, base) };
+    pairs
+        .entry((first.to_owned(), second.to_owned()))
+        .or_insert_with(|| PairLiquidity {
+            base: first.to_owned(),
+            rel: second.to_owned(),
+            ..Default::default()
+        })
+}
+
+/// Collects the pairs having at least one live order.
+/// The orders of the banned pubkeys and of the pubkeys that didn't send a keep-alive for `maker_order_timeout`
+/// are skipped the same way as the ordermatch loop removes them.
+/// Our own orders are counted only in `my_orders`, so they don't look like the liquidity we can match with.
+fn pairs_liquidity<'a>(
+    orderbook: &Orderbook,
+    my_maker_orders: impl Iterator<Item = &'a MakerOrder>,
+    my_pubkey: &str,
+    is_pubkey_banned: impl Fn(&str) -> bool,
+    maker_order_timeout: u64,
+    now: u64,
+) -> Vec<PairLiquidity> {
+    let mut pairs = BTreeMap::new();
+
+    for ((base, rel), uuids) in orderbook.unordered.iter() {
+        let orders = uuids
+            .iter()
+            .filter_map(|uuid| orderbook.order_set.get(uuid))
+            .filter(|order| {
+                if order.pubkey == my_pubkey {
+                    return false;
+                }
+                let alive = match orderbook.pubkeys_state.get(&order.pubkey) {
+                    Some(state) => state.last_keep_alive + maker_order_timeout > now,
+                    None => false,
+                };
+                alive && !is_pubkey_banned(&order.pubkey)
+            })
+            .count();
+        if orders == 0 {
+            continue;
+        }
+
+        let entry = pair_entry(&mut pairs, base, rel);
+        if base == &entry.base {
+            entry.asks += orders;
+        } else {
+            entry.bids += orders;
+        }
+    }
+
+    for order in my_maker_orders {
+        pair_entry(&mut pairs, &order.base, &order.rel).my_orders += 1;
+    }
+
+    pairs.into_iter().map(|(_, liquidity)| liquidity).collect()
+}
+
+/// Whether both coins of the pair are enabled, not wallet only and the pair is allowed by the pair filter
+/// at least in one direction.
+async fn is_pair_tradable(ctx: &MmArc, pair_filter: &PairFilter, base: &str, rel: &str) -> bool {
+    for ticker in [base, rel].iter() {
+        match lp_coinfind(ctx, ticker).await {
+            Ok(Some(coin)) if !coin.wallet_only(ctx) => (),
+            _ => return false,
+        }
+    }
+    pair_filter.is_allowed(base, rel) || pair_filter.is_allowed(rel, base)
+}
+
+pub async fn matchable_pairs_rpc(ctx: MmArc, _req: Json) -> Result<Response<Vec<u8>>, String> {
+    let ordermatch_ctx = try_s!(OrdermatchContext::from_ctx(&ctx));
+    let my_pubkey = hex::encode(&**ctx.secp256k1_key_pair().public());
+    let maker_order_timeout = ctx.conf["maker_order_timeout"].as_u64().unwrap_or(MAKER_ORDER_TIMEOUT);
+    let is_banned = |pubkey: &str| match hex::decode(pubkey) {
+        Ok(bytes) if bytes.len() == 33 => is_pubkey_banned(&ctx, &bytes[1..].into()),
+        _ => true,
+    };
+
+    let pairs = {
+        let orderbook = ordermatch_ctx.orderbook.lock().await;
+        let my_maker_orders = ordermatch_ctx.my_maker_orders.lock().await;
+        pairs_liquidity(
+            &orderbook,
+            my_maker_orders.values(),
+            &my_pubkey,
+            is_banned,
+            maker_order_timeout,
+            now_ms() / 1000,
+        )
+    };
+
+    let pair_filter = ordermatch_ctx.pair_filter.lock().await.clone();
+    let mut result = Vec::with_capacity(pairs.len());
+    for pair in pairs {
+        if is_pair_tradable(&ctx, &pair_filter, &pair.base, &pair.rel).await {
+            result.push(pair);
+        }
+    }
+
+    let res = json!({ "result": result });
+    Response::builder()
+        .body(json::to_vec(&res).expect("Serialization failed"))
+        .map_err(|e| ERRL!("{}", e))
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod matchable_pairs_tests {
+    use super::*;
+    use crate::mm2::lp_ordermatch::ordermatch_tests::orderbook_item_for_test;
+    use crate::mm2::lp_ordermatch::MakerOrderBuilder;
+    use coins::{MmCoinEnum, TestCoin};
+
+    #[test]
+    fn test_pairs_liquidity() {
+        let mut orderbook = Orderbook::default();
+        orderbook.insert_or_update_order_update_trie(orderbook_item_for_test("alive", "RICK", "MORTY"));
+        orderbook.insert_or_update_order_update_trie(orderbook_item_for_test("alive", "RICK", "MORTY"));
+        orderbook.insert_or_update_order_update_trie(orderbook_item_for_test("alive", "MORTY", "RICK"));
+        orderbook.insert_or_update_order_update_trie(orderbook_item_for_test("banned", "RICK", "ETH"));
+        orderbook.insert_or_update_order_update_trie(orderbook_item_for_test("expired", "RICK", "JST"));
+        orderbook.insert_or_update_order_update_trie(orderbook_item_for_test("mine", "MORTY", "ETH"));
+        for (pubkey, last_keep_alive) in [("alive", 95), ("banned", 95), ("expired", 50)].iter() {
+            orderbook.pubkeys_state.get_mut(*pubkey).unwrap().last_keep_alive = *last_keep_alive;
+        }
+        orderbook.pubkeys_state.get_mut("mine").unwrap().last_keep_alive = 0;
+
+        let rick: MmCoinEnum = TestCoin::new("RICK").into();
+        let dex: MmCoinEnum = TestCoin::new("DEX").into();
+        let morty: MmCoinEnum = TestCoin::new("MORTY").into();
+        let eth: MmCoinEnum = TestCoin::new("ETH").into();
+        let my_orders = vec![
+            MakerOrderBuilder::new(&rick, &dex).build_unchecked(),
+            MakerOrderBuilder::new(&dex, &rick).build_unchecked(),
+            // our order is in the orderbook too, but it's not counted as a bid
+            MakerOrderBuilder::new(&morty, &eth).build_unchecked(),
+        ];
+
+        let pairs = pairs_liquidity(
+            &orderbook,
+            my_orders.iter(),
+            "mine",
+            |pubkey| pubkey == "banned",
+            30,
+            100,
+        );
+        let expected = vec![
+            PairLiquidity {
+                base: "DEX".into(),
+                rel: "RICK".into(),
+                my_orders: 2,
+                ..Default::default()
+            },
+            PairLiquidity {
+                base: "ETH".into(),
+                rel: "MORTY".into(),
+                my_orders: 1,
+                ..Default::default()
+            },
+            PairLiquidity {
+                base: "MORTY".into(),
+                rel: "RICK".into(),
+                asks: 1,
+                bids: 2,
+                ..Default::default()
+            },
+        ];
+        assert_eq!(pairs, expected);
+    }
+}
@@ -0,0 +1,170 @@
+use super::{MakerOrder, MakerOrderForMyOrdersRpc, OrderbookItem, OrdermatchContext, TakerOrder, TakerOrderForRpc};
+use bigdecimal::BigDecimal;
+use common::mm_ctx::MmArc;
+use common::mm_number::MmNumber;
+use http::Response;
+use num_rational::BigRational;
+use serde_json::{self as json, Value as Json};
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+struct OrderByUuidReq {
+    uuid: Uuid,
+}
+
+/// The order found by [`order_status_by_uuid`] with the source it was found in.
+#[derive(Debug)]
+enum FoundOrder {
+    MyMaker(MakerOrder),
+    MyTaker(TakerOrder),
+    /// The order of the orderbook, it's ours if it was created by our pubkey
+    Orderbook(OrderbookItem),
+    /// The order that is not in the orderbook anymore, e.g. after the maker pubkey keep-alive timeout
+    Inactive(OrderbookItem),
+}
+
+#[derive(Serialize)]
+struct OrderbookItemForRpc<'a> {
+    base: &'a str,
+    rel: &'a str,
+    price: BigDecimal,
+    price_rat: &'a BigRational,
+    max_volume: BigDecimal,
+    max_volume_rat: &'a BigRational,
+    min_volume: BigDecimal,
+    min_volume_rat: &'a BigRational,
+    pubkey: &'a str,
+    uuid: Uuid,
+    created_at: u64,
+}
+
+impl<'a> From<&'a OrderbookItem> for OrderbookItemForRpc<'a> {
+    fn from(order: &'a OrderbookItem) -> OrderbookItemForRpc<'a> {
+        OrderbookItemForRpc {
+            base: &order.base,
+            rel: &order.rel,
+            price: MmNumber::from(order.price.clone()).to_decimal(),
+            price_rat: &order.price,
+            max_volume: MmNumber::from(order.max_volume.clone()).to_decimal(),
+            max_volume_rat: &order.max_volume,
+            min_volume: MmNumber::from(order.min_volume.clone()).to_decimal(),
+            min_volume_rat: &order.min_volume,
+            pubkey: &order.pubkey,
+            uuid: order.uuid,
+            created_at: order.created_at,
+        }
+    }
+}
+
+/// Looks for the order in our maker and taker orders, then in the orderbook and then in the inactive orders.
+/// The mutexes are locked one by one, so the order moved between them concurrently might be missed.
+async fn order_status_by_uuid(ctx: &MmArc, uuid: &Uuid) -> Result<Option<FoundOrder>, String> {
+    let ordermatch_ctx = try_s!(OrdermatchContext::from_ctx(ctx));
+    if let Some(order) = ordermatch_ctx.my_maker_orders.lock().await.get(uuid) {
+        return Ok(Some(FoundOrder::MyMaker(order.clone())));
+    }
+    if let Some(order) = ordermatch_ctx.my_taker_orders.lock().await.get(uuid) {
+        return Ok(Some(FoundOrder::MyTaker(order.clone())));
+    }
+    if let Some(order) = ordermatch_ctx.orderbook.lock().await.find_order_by_uuid(uuid) {
+        return Ok(Some(FoundOrder::Orderbook(order)));
+    }
+    if let Some(order) = ordermatch_ctx.inactive_orders.lock().await.get(uuid) {
+        return Ok(Some(FoundOrder::Inactive(order.clone())));
+    }
+    Ok(None)
+}
+
+pub async fn order_by_uuid_rpc(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, String> {
+    let req: OrderByUuidReq = try_s!(json::from_value(req));
+    let my_pubkey = hex::encode(&**ctx.secp256k1_key_pair().public());
+
+    let result = match try_s!(order_status_by_uuid(&ctx, &req.uuid).await) {
+        Some(FoundOrder::MyMaker(order)) => json!({
+            "source": "my_maker_orders",
+            "is_mine": true,
+            "order": MakerOrderForMyOrdersRpc::from(&order),
+        }),
+        Some(FoundOrder::MyTaker(order)) => json!({
+            "source": "my_taker_orders",
+            "is_mine": true,
+            "order": TakerOrderForRpc::from(&order),
+        }),
+        Some(FoundOrder::Orderbook(order)) => json!({
+            "source": "orderbook",
+            "is_mine": order.pubkey == my_pubkey,
+            "order": OrderbookItemForRpc::from(&order),
+        }),
+        Some(FoundOrder::Inactive(order)) => json!({
+            "source": "inactive_orders",
+            "is_mine": order.pubkey == my_pubkey,
+            "order": OrderbookItemForRpc::from(&order),
+        }),
+        None => {
+            let res = json!({
+                "error": format!("Order with uuid {} is not found", req.uuid),
+            });
+            return Response::builder()
+                .status(404)
+                .body(json::to_vec(&res).expect("Serialization failed"))
+                .map_err(|e| ERRL!("{}", e));
+        },
+    };
+
+    let res = json!({ "result": result });
+    Response::builder()
+        .body(json::to_vec(&res).expect("Serialization failed"))
+        .map_err(|e| ERRL!("{}", e))
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod order_by_uuid_tests {
+    use super::*;
+    use crate::mm2::lp_ordermatch::ordermatch_tests::orderbook_item_for_test;
+    use crate::mm2::lp_ordermatch::{MakerOrderBuilder, TakerOrderBuilder};
+    use coins::{MmCoinEnum, TestCoin};
+    use common::block_on;
+    use common::mm_ctx::MmCtxBuilder;
+
+    #[test]
+    fn test_order_status_by_uuid() {
+        let ctx = MmCtxBuilder::default().into_mm_arc();
+        let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+        let rick: MmCoinEnum = TestCoin::new("RICK").into();
+        let morty: MmCoinEnum = TestCoin::new("MORTY").into();
+
+        let maker_order = MakerOrderBuilder::new(&rick, &morty).build_unchecked();
+        let maker_uuid = maker_order.uuid;
+        block_on(ordermatch_ctx.my_maker_orders.lock()).insert(maker_uuid, maker_order);
+
+        let taker_order = TakerOrderBuilder::new(&rick, &morty).build_unchecked();
+        let taker_uuid = taker_order.request.uuid;
+        block_on(ordermatch_ctx.my_taker_orders.lock()).insert(taker_uuid, taker_order);
+
+        let orderbook_order = orderbook_item_for_test("pubkey", "RICK", "MORTY");
+        let orderbook_uuid = orderbook_order.uuid;
+        block_on(ordermatch_ctx.orderbook.lock()).insert_or_update_order_update_trie(orderbook_order);
+
+        let inactive_order = orderbook_item_for_test("pubkey", "RICK", "MORTY");
+        let inactive_uuid = inactive_order.uuid;
+        block_on(ordermatch_ctx.inactive_orders.lock()).insert(inactive_uuid, inactive_order);
+
+        match block_on(order_status_by_uuid(&ctx, &maker_uuid)).unwrap() {
+            Some(FoundOrder::MyMaker(order)) => assert_eq!(order.uuid, maker_uuid),
+            found => panic!("Expected my maker order, found {:?}", found),
+        }
+        match block_on(order_status_by_uuid(&ctx, &taker_uuid)).unwrap() {
+            Some(FoundOrder::MyTaker(order)) => assert_eq!(order.request.uuid, taker_uuid),
+            found => panic!("Expected my taker order, found {:?}", found),
+        }
+        match block_on(order_status_by_uuid(&ctx, &orderbook_uuid)).unwrap() {
+            Some(FoundOrder::Orderbook(order)) => assert_eq!(order.uuid, orderbook_uuid),
+            found => panic!("Expected the orderbook order, found {:?}", found),
+        }
+        match block_on(order_status_by_uuid(&ctx, &inactive_uuid)).unwrap() {
+            Some(FoundOrder::Inactive(order)) => assert_eq!(order.uuid, inactive_uuid),
+            found => panic!("Expected the inactive order, found {:?}", found),
+        }
+        assert!(block_on(order_status_by_uuid(&ctx, &Uuid::new_v4())).unwrap().is_none());
+    }
+}
@@ -1,4 +1,4 @@
-use super::{orderbook_topic_from_base_rel, OrdermatchContext, OrdermatchRequest};
+use super::{canonical_ticker, orderbook_topic_from_base_rel, OrdermatchContext, OrdermatchRequest};
 use crate::mm2::lp_network::{request_any_relay, P2PRequest};
 use coins::is_wallet_only_ticker;
 use common::{log, mm_ctx::MmArc};
@@ -30,7 +30,11 @@ struct PairWithDepth {
 
 pub async fn orderbook_depth_rpc(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, String> {
     let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
-    let req: OrderbookDepthReq = try_s!(json::from_value(req));
+    let mut req: OrderbookDepthReq = try_s!(json::from_value(req));
+    for (base, rel) in req.pairs.iter_mut() {
+        *base = canonical_ticker(&ctx, base);
+        *rel = canonical_ticker(&ctx, rel);
+    }
 
     let wallet_only_pairs: Vec<_> = req
         .pairs
@@ -0,0 +1,136 @@
+use super::{canonical_ticker, subscribe_to_orderbook_topic, OrderbookEvent, OrdermatchContext};
+use common::custom_futures::FutureTimerExt;
+use common::mm_ctx::MmArc;
+use futures::channel::mpsc::Receiver;
+use futures::StreamExt;
+use http::Response;
+use serde_json::{self as json, Value as Json};
+use std::collections::BTreeMap;
+
+/// The max number of the subscriptions, the oldest one is closed on the new subscription over the limit.
+const MAX_ORDERBOOK_EVENTS_SUBSCRIPTIONS: usize = 10;
+const DEFAULT_ORDERBOOK_EVENTS_TIMEOUT: u64 = 30;
+const MAX_ORDERBOOK_EVENTS_TIMEOUT: u64 = 60;
+
+/// The receivers of the [`OrderbookEvent`]s subscribed by [`orderbook_events_subscribe_rpc`].
+#[derive(Default)]
+pub struct OrderbookEventsSubscriptions {
+    next_id: u64,
+    /// The ids are increasing, so the first receiver is of the oldest subscription
+    receivers: BTreeMap<u64, Receiver<OrderbookEvent>>,
+}
+
+impl OrderbookEventsSubscriptions {
+    fn add(&mut self, rx: Receiver<OrderbookEvent>) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.receivers.insert(id, rx);
+        while self.receivers.len() > MAX_ORDERBOOK_EVENTS_SUBSCRIPTIONS {
+            let oldest = *self.receivers.keys().next().expect("receivers are not empty");
+            self.receivers.remove(&oldest);
+        }
+        id
+    }
+}
+
+#[derive(Deserialize)]
+struct OrderbookEventsSubscribeReq {
+    /// Only the events of this pair orders are sent if set, the pair matches both asks and bids
+    base: Option<String>,
+    rel: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OrderbookEventsReq {
+    subscription_id: u64,
+    /// The max number of seconds to wait for an event
+    timeout: Option<u64>,
+}
+
+/// Waits for the first event up to `timeout` seconds, then takes all buffered events.
+/// Returns None if the subscription was closed.
+async fn poll_events(rx: &mut Receiver<OrderbookEvent>, timeout: u64) -> Option<Vec<OrderbookEvent>> {
+    let first = match rx.next().timeout_secs(timeout as f64).await {
+        Ok(Some(event)) => event,
+        Ok(None) => return None,
+        Err(_timeout) => return Some(Vec::new()),
+    };
+    let mut events = vec![first];
+    loop {
+        match rx.try_next() {
+            Ok(Some(event)) => events.push(event),
+            Ok(None) => return None,
+            // no more buffered events
+            Err(_) => break,
+        }
+    }
+    Some(events)
+}
+
+pub async fn orderbook_events_subscribe_rpc(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, String> {
+    let req: OrderbookEventsSubscribeReq = try_s!(json::from_value(req));
+    let pair = match (req.base, req.rel) {
+        (Some(base), Some(rel)) => {
+            let (base, rel) = (canonical_ticker(&ctx, &base), canonical_ticker(&ctx, &rel));
+            if base == rel {
+                return ERR!("Base and rel must be different coins");
+            }
+            // the network events of the pair are received only if we are subscribed to its topic
+            let request_orderbook = true;
+            try_s!(subscribe_to_orderbook_topic(&ctx, &base, &rel, request_orderbook).await);
+            Some((base, rel))
+        },
+        (None, None) => None,
+        _ => return ERR!("Both base and rel must be set to filter the events by pair"),
+    };
+
+    let ordermatch_ctx = try_s!(OrdermatchContext::from_ctx(&ctx));
+    let rx = ordermatch_ctx.orderbook.lock().await.subscribe_events(pair);
+    let subscription_id = ordermatch_ctx.orderbook_events_subscriptions.lock().await.add(rx);
+    let res = json!({ "result": { "subscription_id": subscription_id } });
+    Response::builder()
+        .body(json::to_vec(&res).expect("Serialization failed"))
+        .map_err(|e| ERRL!("{}", e))
+}
+
+/// Long polls the events of the subscription created by [`orderbook_events_subscribe_rpc`].
+pub async fn orderbook_events_rpc(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, String> {
+    let req: OrderbookEventsReq = try_s!(json::from_value(req));
+    let timeout = req
+        .timeout
+        .unwrap_or(DEFAULT_ORDERBOOK_EVENTS_TIMEOUT)
+        .min(MAX_ORDERBOOK_EVENTS_TIMEOUT);
+
+    let ordermatch_ctx = try_s!(OrdermatchContext::from_ctx(&ctx));
+    // the receiver is taken out while waiting to not hold the lock, it's returned back unless the subscription is closed
+    let mut rx = match ordermatch_ctx
+        .orderbook_events_subscriptions
+        .lock()
+        .await
+        .receivers
+        .remove(&req.subscription_id)
+    {
+        Some(rx) => rx,
+        None => return ERR!("Subscription {} is not found or is polled already", req.subscription_id),
+    };
+    let events = match poll_events(&mut rx, timeout).await {
+        Some(events) => events,
+        None => {
+            return ERR!(
+                "Subscription {} is closed as the events buffer overflowed, resubscribe and reload the orderbook",
+                req.subscription_id
+            )
+        },
+    };
+    ordermatch_ctx
+        .orderbook_events_subscriptions
+        .lock()
+        .await
+        .receivers
+        .insert(req.subscription_id, rx);
+
+    let res = json!({ "result": { "events": events } });
+    Response::builder()
+        .body(json::to_vec(&res).expect("Serialization failed"))
+        .map_err(|e| ERRL!("{}", e))
+}
@@ -1,4 +1,5 @@
-use super::{subscribe_to_orderbook_topic, OrdermatchContext, RpcOrderbookEntry};
+use super::{canonical_ticker, orderbook_topic_from_base_rel, subscribe_to_orderbook_topic, OrdermatchContext,
+            RpcOrderbookEntry};
 use coins::{address_by_coin_conf_and_pubkey_str, coin_conf, is_wallet_only_conf};
 use common::{mm_ctx::MmArc, mm_number::MmNumber, now_ms};
 use http::Response;
@@ -44,6 +45,9 @@ pub struct OrderbookResponse {
     #[serde(rename = "numbids")]
     num_bids: usize,
     rel: String,
+    /// The share of the expected relays that responded to the orderbook requests,
+    /// None if the orderbook was not requested from relays (e.g. synced by the pubkeys keep alive messages).
+    sync_confidence: Option<f64>,
     timestamp: u64,
     #[serde(flatten)]
     total_asks_base: TotalAsksBaseVol,
@@ -74,7 +78,9 @@ fn build_aggregated_entries(entries: Vec<RpcOrderbookEntry>) -> (Vec<AggregatedO
 }
 
 pub async fn orderbook_rpc(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, String> {
-    let req: OrderbookReq = try_s!(json::from_value(req));
+    let mut req: OrderbookReq = try_s!(json::from_value(req));
+    req.base = canonical_ticker(&ctx, &req.base);
+    req.rel = canonical_ticker(&ctx, &req.rel);
     if req.base == req.rel {
         return ERR!("Base and rel must be different coins");
     }
@@ -145,6 +151,7 @@ pub async fn orderbook_rpc(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, S
     };
     bids.sort_unstable_by(|bid1, bid2| bid2.price_rat.cmp(&bid1.price_rat));
     let (bids, total_bids_base_vol, total_bids_rel_vol) = build_aggregated_entries(bids);
+    let sync_confidence = orderbook.sync_confidence(&orderbook_topic_from_base_rel(&req.base, &req.rel));
 
     let response = OrderbookResponse {
         num_asks: asks.len(),
@@ -156,6 +163,7 @@ pub async fn orderbook_rpc(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, S
         bids,
         netid: ctx.netid(),
         rel: req.rel,
+        sync_confidence,
         timestamp: now_ms() / 1000,
         total_asks_base: total_asks_base_vol.into(),
         total_asks_rel: total_asks_rel_vol.into(),
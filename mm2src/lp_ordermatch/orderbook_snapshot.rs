@@ -0,0 +1,342 @@
+use super::{canonical_ticker, subscribe_to_orderbook_topic, Orderbook, OrderbookItem, OrdermatchContext};
+use bigdecimal::BigDecimal;
+use coins::is_wallet_only_ticker;
+use common::{mm_ctx::MmArc, mm_number::MmNumber, now_ms};
+use http::Response;
+use serde_json::{self as json, Value as Json};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// The version of the [`OrderbookSnapshot`] schema.
+/// Must be incremented on every change of the serialized snapshot layout.
+const ORDERBOOK_SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Deserialize)]
+struct OrderbookSnapshotReq {
+    base: String,
+    rel: String,
+}
+
+/// The orderbook entry of the snapshot, the `price` is in `rel` coin per 1 `base` coin,
+/// the volumes are in `base` coin for both asks and bids.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct OrderbookSnapshotEntry {
+    price: BigDecimal,
+    volume: BigDecimal,
+    min_volume: BigDecimal,
+    pubkey: String,
+    uuid: Uuid,
+    /// The number of seconds since the order was created
+    age: u64,
+}
+
+impl OrderbookSnapshotEntry {
+    fn from_ask(order: &OrderbookItem, now: u64) -> OrderbookSnapshotEntry {
+        OrderbookSnapshotEntry {
+            price: MmNumber::from(order.price.clone()).to_decimal(),
+            volume: MmNumber::from(order.max_volume.clone()).to_decimal(),
+            min_volume: MmNumber::from(order.min_volume.clone()).to_decimal(),
+            pubkey: order.pubkey.clone(),
+            uuid: order.uuid,
+            age: now.saturating_sub(order.created_at),
+        }
+    }
+
+    /// The bid is the order to sell `rel` for `base`, so its price and volumes have to be inverted.
+    fn from_bid(order: &OrderbookItem, now: u64) -> OrderbookSnapshotEntry {
+        let price = MmNumber::from(order.price.clone());
+        OrderbookSnapshotEntry {
+            price: (MmNumber::from(1) / price.clone()).to_decimal(),
+            volume: (&MmNumber::from(order.max_volume.clone()) * &price).to_decimal(),
+            min_volume: (&MmNumber::from(order.min_volume.clone()) * &price).to_decimal(),
+            pubkey: order.pubkey.clone(),
+            uuid: order.uuid,
+            age: now.saturating_sub(order.created_at),
+        }
+    }
+}
+
+/// The compact snapshot of the `base`/`rel` orderbook for the external consumers.
+/// Doesn't depend on the [`OrderbookItem`] encoding, so it stays stable when the P2P protocol changes.
+#[derive(Debug, Deserialize, Serialize)]
+struct OrderbookSnapshot {
+    version: u32,
+    base: String,
+    rel: String,
+    timestamp: u64,
+    /// Sorted by price from the lowest to the highest
+    asks: Vec<OrderbookSnapshotEntry>,
+    /// Sorted by price from the highest to the lowest
+    bids: Vec<OrderbookSnapshotEntry>,
+}
+
+impl OrderbookSnapshot {
+    fn from_orderbook(orderbook: &Orderbook, base: &str, rel: &str, now: u64) -> OrderbookSnapshot {
+        let orders = |base: &str, rel: &str| -> Vec<&OrderbookItem> {
+            match orderbook.unordered.get(&(base.to_owned(), rel.to_owned())) {
+                Some(uuids) => uuids.iter().filter_map(|uuid| orderbook.order_set.get(uuid)).collect(),
+                None => Vec::new(),
+            }
+        };
+
+        let mut asks: Vec<_> = orders(base, rel)
+            .into_iter()
+            .map(|order| OrderbookSnapshotEntry::from_ask(order, now))
+            .collect();
+        asks.sort_by(|ask1, ask2| ask1.price.cmp(&ask2.price).then(ask1.uuid.cmp(&ask2.uuid)));
+
+        let mut bids: Vec<_> = orders(rel, base)
+            .into_iter()
+            .map(|order| OrderbookSnapshotEntry::from_bid(order, now))
+            .collect();
+        bids.sort_by(|bid1, bid2| bid2.price.cmp(&bid1.price).then(bid1.uuid.cmp(&bid2.uuid)));
+
+        OrderbookSnapshot {
+            version: ORDERBOOK_SNAPSHOT_VERSION,
+            base: base.to_owned(),
+            rel: rel.to_owned(),
+            timestamp: now,
+            asks,
+            bids,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct OrderbookSnapshotDiffReq {
+    old: OrderbookSnapshot,
+    new: OrderbookSnapshot,
+}
+
+#[derive(Debug, Serialize)]
+struct RepricedOrder {
+    uuid: Uuid,
+    old_price: BigDecimal,
+    new_price: BigDecimal,
+}
+
+/// The changes of the asks or bids between two snapshots.
+#[derive(Debug, Default, Serialize)]
+struct OrderbookSideDiff {
+    added: Vec<OrderbookSnapshotEntry>,
+    removed: Vec<OrderbookSnapshotEntry>,
+    repriced: Vec<RepricedOrder>,
+}
+
+impl OrderbookSideDiff {
+    fn new(old: &[OrderbookSnapshotEntry], new: &[OrderbookSnapshotEntry]) -> OrderbookSideDiff {
+        let old_by_uuid: HashMap<_, _> = old.iter().map(|entry| (entry.uuid, entry)).collect();
+        let new_by_uuid: HashMap<_, _> = new.iter().map(|entry| (entry.uuid, entry)).collect();
+
+        let mut diff = OrderbookSideDiff::default();
+        // iterate the entries in the snapshot order to keep the diff sorted by price
+        for entry in new.iter() {
+            match old_by_uuid.get(&entry.uuid) {
+                Some(old_entry) if old_entry.price != entry.price => diff.repriced.push(RepricedOrder {
+                    uuid: entry.uuid,
+                    old_price: old_entry.price.clone(),
+                    new_price: entry.price.clone(),
+                }),
+                Some(_) => (),
+                None => diff.added.push(entry.clone()),
+            }
+        }
+        diff.removed = old
+            .iter()
+            .filter(|entry| !new_by_uuid.contains_key(&entry.uuid))
+            .cloned()
+            .collect();
+        diff
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct OrderbookSnapshotDiff {
+    base: String,
+    rel: String,
+    old_timestamp: u64,
+    new_timestamp: u64,
+    asks: OrderbookSideDiff,
+    bids: OrderbookSideDiff,
+}
+
+impl OrderbookSnapshotDiff {
+    fn new(old: &OrderbookSnapshot, new: &OrderbookSnapshot) -> Result<OrderbookSnapshotDiff, String> {
+        if old.version != new.version {
+            return ERR!("Snapshot versions {} and {} are different", old.version, new.version);
+        }
+        if old.base != new.base || old.rel != new.rel {
+            return ERR!(
+                "Snapshot pairs {}/{} and {}/{} are different",
+                old.base,
+                old.rel,
+                new.base,
+                new.rel
+            );
+        }
+        Ok(OrderbookSnapshotDiff {
+            base: new.base.clone(),
+            rel: new.rel.clone(),
+            old_timestamp: old.timestamp,
+            new_timestamp: new.timestamp,
+            asks: OrderbookSideDiff::new(&old.asks, &new.asks),
+            bids: OrderbookSideDiff::new(&old.bids, &new.bids),
+        })
+    }
+}
+
+pub async fn orderbook_snapshot_rpc(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, String> {
+    let mut req: OrderbookSnapshotReq = try_s!(json::from_value(req));
+    req.base = canonical_ticker(&ctx, &req.base);
+    req.rel = canonical_ticker(&ctx, &req.rel);
+    if req.base == req.rel {
+        return ERR!("Base and rel must be different coins");
+    }
+    if is_wallet_only_ticker(&ctx, &req.base) || is_wallet_only_ticker(&ctx, &req.rel) {
+        return ERR!("Pair {}/{} has wallet only coins", req.base, req.rel);
+    }
+
+    let request_orderbook = true;
+    try_s!(subscribe_to_orderbook_topic(&ctx, &req.base, &req.rel, request_orderbook).await);
+    let ordermatch_ctx = try_s!(OrdermatchContext::from_ctx(&ctx));
+    let snapshot = OrderbookSnapshot::from_orderbook(
+        &*ordermatch_ctx.orderbook.lock().await,
+        &req.base,
+        &req.rel,
+        now_ms() / 1000,
+    );
+    let res = json!({ "result": snapshot });
+    Response::builder()
+        .body(json::to_vec(&res).expect("Serialization failed"))
+        .map_err(|e| ERRL!("{}", e))
+}
+
+/// Compares two snapshots returned by [`orderbook_snapshot_rpc`], the snapshots must be of the same pair and version.
+pub async fn orderbook_snapshot_diff_rpc(_ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, String> {
+    let req: OrderbookSnapshotDiffReq = try_s!(json::from_value(req));
+    let diff = try_s!(OrderbookSnapshotDiff::new(&req.old, &req.new));
+    let res = json!({ "result": diff });
+    Response::builder()
+        .body(json::to_vec(&res).expect("Serialization failed"))
+        .map_err(|e| ERRL!("{}", e))
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod orderbook_snapshot_tests {
+    use super::*;
+    use crate::mm2::lp_ordermatch::ordermatch_tests::orderbook_item_for_test;
+    use num_rational::BigRational;
+
+    /// The order with the volume from 1 to 10 `base` coins.
+    fn orderbook_item(base: &str, rel: &str, price: BigRational, created_at: u64) -> OrderbookItem {
+        let mut order = orderbook_item_for_test("pubkey", base, rel);
+        order.price = price;
+        order.min_volume = BigRational::from_integer(1.into());
+        order.created_at = created_at;
+        order
+    }
+
+    #[test]
+    fn test_orderbook_snapshot_schema() {
+        let mut orderbook = Orderbook::default();
+        orderbook.insert_or_update_order_update_trie(orderbook_item(
+            "RICK",
+            "MORTY",
+            BigRational::from_integer(2.into()),
+            90,
+        ));
+        orderbook.insert_or_update_order_update_trie(orderbook_item(
+            "RICK",
+            "MORTY",
+            BigRational::from_integer(1.into()),
+            80,
+        ));
+        // the bid to buy 5 RICK for 10 MORTY at 2 MORTY per RICK
+        orderbook.insert_or_update_order_update_trie(orderbook_item(
+            "MORTY",
+            "RICK",
+            BigRational::new(1.into(), 2.into()),
+            70,
+        ));
+
+        let snapshot = OrderbookSnapshot::from_orderbook(&orderbook, "RICK", "MORTY", 100);
+        let asks_uuids: Vec<_> = snapshot.asks.iter().map(|ask| ask.uuid).collect();
+        let bid_uuid = snapshot.bids[0].uuid;
+        let actual = json::to_value(&snapshot).unwrap();
+        let expected = json!({
+            "version": 1,
+            "base": "RICK",
+            "rel": "MORTY",
+            "timestamp": 100,
+            "asks": [
+                {
+                    "price": "1",
+                    "volume": "10",
+                    "min_volume": "1",
+                    "pubkey": "pubkey",
+                    "uuid": asks_uuids[0],
+                    "age": 20,
+                },
+                {
+                    "price": "2",
+                    "volume": "10",
+                    "min_volume": "1",
+                    "pubkey": "pubkey",
+                    "uuid": asks_uuids[1],
+                    "age": 10,
+                },
+            ],
+            "bids": [
+                {
+                    "price": "2",
+                    "volume": "5",
+                    "min_volume": "0.5",
+                    "pubkey": "pubkey",
+                    "uuid": bid_uuid,
+                    "age": 30,
+                },
+            ],
+        });
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_orderbook_snapshot_diff() {
+        let mut orderbook = Orderbook::default();
+        let removed = orderbook_item("RICK", "MORTY", BigRational::from_integer(1.into()), 80);
+        let mut repriced = orderbook_item("RICK", "MORTY", BigRational::from_integer(2.into()), 80);
+        let unchanged = orderbook_item("MORTY", "RICK", BigRational::new(1.into(), 2.into()), 80);
+        orderbook.insert_or_update_order_update_trie(removed.clone());
+        orderbook.insert_or_update_order_update_trie(repriced.clone());
+        orderbook.insert_or_update_order_update_trie(unchanged);
+        // the snapshots are passed to the diff RPC in the serialized form
+        let old = OrderbookSnapshot::from_orderbook(&orderbook, "RICK", "MORTY", 90);
+        let old: OrderbookSnapshot = json::from_value(json::to_value(&old).unwrap()).unwrap();
+
+        orderbook.remove_order_trie_update(removed.uuid);
+        repriced.price = BigRational::from_integer(3.into());
+        orderbook.insert_or_update_order_update_trie(repriced.clone());
+        let added = orderbook_item("MORTY", "RICK", BigRational::new(1.into(), 4.into()), 95);
+        orderbook.insert_or_update_order_update_trie(added.clone());
+        let new = OrderbookSnapshot::from_orderbook(&orderbook, "RICK", "MORTY", 100);
+
+        let diff = OrderbookSnapshotDiff::new(&old, &new).unwrap();
+        assert!(diff.asks.added.is_empty());
+        assert_eq!(diff.asks.removed.len(), 1);
+        assert_eq!(diff.asks.removed[0].uuid, removed.uuid);
+        assert_eq!(diff.asks.repriced.len(), 1);
+        assert_eq!(diff.asks.repriced[0].uuid, repriced.uuid);
+        assert_eq!(diff.asks.repriced[0].old_price, BigDecimal::from(2));
+        assert_eq!(diff.asks.repriced[0].new_price, BigDecimal::from(3));
+
+        // the bid to buy RICK at 4 MORTY per RICK
+        assert_eq!(diff.bids.added.len(), 1);
+        assert_eq!(diff.bids.added[0].uuid, added.uuid);
+        assert_eq!(diff.bids.added[0].price, BigDecimal::from(4));
+        assert!(diff.bids.removed.is_empty());
+        assert!(diff.bids.repriced.is_empty());
+
+        let other_pair = OrderbookSnapshot::from_orderbook(&orderbook, "MORTY", "RICK", 100);
+        assert!(OrderbookSnapshotDiff::new(&old, &other_pair).is_err());
+    }
+}
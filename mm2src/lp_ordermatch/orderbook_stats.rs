@@ -0,0 +1,105 @@
+use super::{Orderbook, OrdermatchContext};
+use common::mm_ctx::MmArc;
+use http::Response;
+use serde_json::{self as json, Value as Json};
+use std::collections::HashMap;
+
+/// The snapshot of the orderbook internals.
+/// Contains the counters only to keep the snapshot cheap.
+#[derive(Debug, Serialize)]
+struct OrderbookStats {
+    /// The number of maker pubkeys tracked by the orderbook
+    pubkeys: usize,
+    /// The total number of orders
+    orders: usize,
+    /// The orderbook topics we are subscribed to
+    subscribed_topics: Vec<String>,
+    /// The total number of the trie diffs kept for all pubkeys
+    diff_history_len: usize,
+    /// The number of the trie diffs kept per pubkey
+    pubkeys_diff_history_len: HashMap<String, usize>,
+    /// The number of orders evicted due to the per pubkey limit
+    evicted_orders: u64,
+}
+
+impl OrderbookStats {
+    fn from_orderbook(orderbook: &Orderbook) -> OrderbookStats {
+        let pubkeys_diff_history_len: HashMap<_, _> = orderbook
+            .pubkeys_state
+            .iter()
+            .map(|(pubkey, state)| {
+                let len = state
+                    .order_pairs_trie_state_history
+                    .values()
+                    .map(|history| history.inner.len())
+                    .sum();
+                (pubkey.clone(), len)
+            })
+            .collect();
+
+        OrderbookStats {
+            pubkeys: orderbook.pubkeys_state.len(),
+            orders: orderbook.order_set.len(),
+            subscribed_topics: orderbook.topics_subscribed_to.keys().cloned().collect(),
+            diff_history_len: pubkeys_diff_history_len.values().sum(),
+            pubkeys_diff_history_len,
+            evicted_orders: orderbook.evicted_orders,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct OrdermatchStats {
+    #[serde(flatten)]
+    orderbook: OrderbookStats,
+    inactive_orders: usize,
+    my_maker_orders: usize,
+    my_taker_orders: usize,
+}
+
+pub async fn orderbook_stats_rpc(ctx: MmArc, _req: Json) -> Result<Response<Vec<u8>>, String> {
+    let ordermatch_ctx = try_s!(OrdermatchContext::from_ctx(&ctx));
+    // lock the mutexes one by one to avoid holding the orderbook lock for long time
+    let orderbook = OrderbookStats::from_orderbook(&*ordermatch_ctx.orderbook.lock().await);
+    let inactive_orders = ordermatch_ctx.inactive_orders.lock().await.len();
+    let my_maker_orders = ordermatch_ctx.my_maker_orders.lock().await.len();
+    let my_taker_orders = ordermatch_ctx.my_taker_orders.lock().await.len();
+
+    let stats = OrdermatchStats {
+        orderbook,
+        inactive_orders,
+        my_maker_orders,
+        my_taker_orders,
+    };
+    let res = json!({ "result": stats });
+    Response::builder()
+        .body(json::to_vec(&res).expect("Serialization failed"))
+        .map_err(|e| ERRL!("{}", e))
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod orderbook_stats_tests {
+    use super::*;
+    use crate::mm2::lp_ordermatch::ordermatch_tests::orderbook_item_for_test;
+    use num_rational::BigRational;
+
+    #[test]
+    fn test_orderbook_stats_from_orderbook() {
+        let mut orderbook = Orderbook::default();
+        let mut order = orderbook_item_for_test("pubkey1", "RICK", "MORTY");
+        orderbook.insert_or_update_order_update_trie(order.clone());
+        // update the order to get a diff in the history
+        order.price = BigRational::from_integer(2.into());
+        orderbook.insert_or_update_order_update_trie(order);
+        orderbook.insert_or_update_order_update_trie(orderbook_item_for_test("pubkey2", "RICK", "MORTY"));
+
+        let stats = OrderbookStats::from_orderbook(&orderbook);
+        assert_eq!(stats.pubkeys, 2);
+        assert_eq!(stats.orders, 2);
+        assert!(stats.subscribed_topics.is_empty());
+        assert_eq!(stats.diff_history_len, 1);
+        assert_eq!(stats.pubkeys_diff_history_len.get("pubkey1"), Some(&1));
+        assert_eq!(stats.pubkeys_diff_history_len.get("pubkey2"), Some(&0));
+        assert_eq!(stats.evicted_orders, 0);
+    }
+}
@@ -0,0 +1,138 @@
+use super::{canonical_ticker_from_conf, OrdermatchContext};
+use common::mm_ctx::MmArc;
+use http::Response;
+use serde_json::{self as json, Value as Json};
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PairFilterEntry {
+    base: String,
+    rel: String,
+}
+
+impl PairFilterEntry {
+    fn matches(&self, base: &str, rel: &str) -> bool { self.base == base && self.rel == rel }
+
+    fn canonicalize(&mut self, conf: &Json) {
+        self.base = canonical_ticker_from_conf(conf, &self.base);
+        self.rel = canonical_ticker_from_conf(conf, &self.rel);
+    }
+}
+
+/// Restricts the `base`/`rel` pairs our node matches the orders on.
+/// Set by the `pair_filter` config field and can be replaced by the `set_pair_filter` RPC.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct PairFilter {
+    /// Only these pairs are allowed if set
+    #[serde(default)]
+    allowlist: Option<Vec<PairFilterEntry>>,
+    /// These pairs are always denied
+    #[serde(default)]
+    denylist: Vec<PairFilterEntry>,
+}
+
+impl PairFilter {
+    pub fn from_conf(conf: &Json) -> Result<PairFilter, String> {
+        let filter: Option<PairFilter> = try_s!(json::from_value(conf["pair_filter"].clone()));
+        let mut filter = filter.unwrap_or_default();
+        filter.canonicalize(conf);
+        Ok(filter)
+    }
+
+    /// Resolves the tickers of the entries to the tickers of the coins `conf`, so "kmd" matches the "KMD" orders.
+    fn canonicalize(&mut self, conf: &Json) {
+        let allowlist = self.allowlist.iter_mut().flatten();
+        for entry in allowlist.chain(self.denylist.iter_mut()) {
+            entry.canonicalize(conf);
+        }
+    }
+
+    pub fn is_allowed(&self, base: &str, rel: &str) -> bool {
+        if self.denylist.iter().any(|entry| entry.matches(base, rel)) {
+            return false;
+        }
+        match &self.allowlist {
+            Some(allowlist) => allowlist.iter().any(|entry| entry.matches(base, rel)),
+            None => true,
+        }
+    }
+}
+
+pub async fn set_pair_filter_rpc(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, String> {
+    let mut filter: PairFilter = try_s!(json::from_value(req));
+    filter.canonicalize(&ctx.conf);
+    let ordermatch_ctx = try_s!(OrdermatchContext::from_ctx(&ctx));
+    *ordermatch_ctx.pair_filter.lock().await = filter;
+    let res = try_s!(json::to_vec(&json!({ "result": "success" })));
+    Ok(try_s!(Response::builder().body(res)))
+}
+
+#[cfg(test)]
+mod pair_filter_tests {
+    use super::*;
+
+    #[test]
+    fn test_pair_filter_not_configured() {
+        let filter = PairFilter::from_conf(&json!({})).unwrap();
+        assert_eq!(filter, PairFilter::default());
+        assert!(filter.is_allowed("RICK", "MORTY"));
+        assert!(filter.is_allowed("MORTY", "RICK"));
+    }
+
+    #[test]
+    fn test_pair_filter_allowlist() {
+        let conf = json!({
+            "pair_filter": {
+                "allowlist": [{"base": "RICK", "rel": "MORTY"}],
+            }
+        });
+        let filter = PairFilter::from_conf(&conf).unwrap();
+        assert!(filter.is_allowed("RICK", "MORTY"));
+        assert!(!filter.is_allowed("MORTY", "RICK"));
+        assert!(!filter.is_allowed("RICK", "ETH"));
+    }
+
+    #[test]
+    fn test_pair_filter_denylist() {
+        let conf = json!({
+            "pair_filter": {
+                "allowlist": [{"base": "RICK", "rel": "MORTY"}, {"base": "RICK", "rel": "ETH"}],
+                "denylist": [{"base": "RICK", "rel": "ETH"}, {"base": "ETH", "rel": "JST"}],
+            }
+        });
+        let filter = PairFilter::from_conf(&conf).unwrap();
+        assert!(filter.is_allowed("RICK", "MORTY"));
+        assert!(!filter.is_allowed("RICK", "ETH"));
+        assert!(!filter.is_allowed("ETH", "JST"));
+
+        let conf = json!({
+            "pair_filter": {
+                "denylist": [{"base": "RICK", "rel": "ETH"}],
+            }
+        });
+        let filter = PairFilter::from_conf(&conf).unwrap();
+        assert!(filter.is_allowed("RICK", "MORTY"));
+        assert!(!filter.is_allowed("RICK", "ETH"));
+    }
+
+    #[test]
+    fn test_pair_filter_canonical_tickers() {
+        let conf = json!({
+            "coins": [{"coin": "KMD"}, {"coin": "BTC"}, {"coin": "ETH"}],
+            "pair_filter": {
+                "allowlist": [{"base": "kmd", "rel": " BTC"}, {"base": "KMD", "rel": "eth"}],
+                "denylist": [{"base": "Kmd", "rel": "Eth"}],
+            }
+        });
+        let filter = PairFilter::from_conf(&conf).unwrap();
+        assert!(filter.is_allowed("KMD", "BTC"));
+        assert!(!filter.is_allowed("KMD", "ETH"));
+        // the tickers missing in the coins config are only trimmed
+        let conf = json!({
+            "pair_filter": {
+                "denylist": [{"base": " RICK ", "rel": "MORTY"}],
+            }
+        });
+        let filter = PairFilter::from_conf(&conf).unwrap();
+        assert!(!filter.is_allowed("RICK", "MORTY"));
+    }
+}
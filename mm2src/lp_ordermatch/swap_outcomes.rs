@@ -0,0 +1,283 @@
+use super::OrdermatchContext;
+use crate::mm2::lp_swap::SavedSwap;
+use common::mm_ctx::MmArc;
+use common::{log, now_ms};
+use http::Response;
+use serde_json::{self as json, Value as Json};
+use std::collections::{HashMap, VecDeque};
+use uuid::Uuid;
+
+/// The max number of the swap outcomes kept in the log, the oldest are removed first.
+const MAX_SWAP_OUTCOMES: usize = 1000;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", content = "data")]
+pub enum SwapOutcome {
+    Success,
+    Failed {
+        event: String,
+        error: String,
+    },
+    TimedOut {
+        event: String,
+        error: String,
+    },
+    /// The swap was stopped before it was finished, e.g. on the node shutdown.
+    Unfinished,
+}
+
+impl SwapOutcome {
+    pub fn from_saved_swap(swap: &SavedSwap) -> SwapOutcome {
+        if !swap.is_finished() {
+            return SwapOutcome::Unfinished;
+        }
+
+        match swap.first_error_event() {
+            Some(event) => {
+                let error = event["data"]["error"].as_str().unwrap_or_default().to_owned();
+                let event = event["type"].as_str().unwrap_or_default().to_owned();
+                if swap.is_timed_out() {
+                    SwapOutcome::TimedOut { event, error }
+                } else {
+                    SwapOutcome::Failed { event, error }
+                }
+            },
+            None => SwapOutcome::Success,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SwapOutcomeRecord {
+    pub uuid: Uuid,
+    pub maker_coin: String,
+    pub taker_coin: String,
+    pub maker_pubkey: String,
+    pub taker_pubkey: String,
+    pub outcome: SwapOutcome,
+    pub recorded_at: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct SwapOutcomesLog {
+    records: VecDeque<SwapOutcomeRecord>,
+}
+
+impl SwapOutcomesLog {
+    pub fn record(&mut self, record: SwapOutcomeRecord) {
+        self.records.push_back(record);
+        while self.records.len() > MAX_SWAP_OUTCOMES {
+            self.records.pop_front();
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct SwapOutcomesAggregate {
+    total: u64,
+    success: u64,
+    failed: u64,
+    timed_out: u64,
+    unfinished: u64,
+    /// The share of the failed and timed out swaps among the total number of swaps
+    fail_rate: f64,
+}
+
+impl SwapOutcomesAggregate {
+    fn add(&mut self, outcome: &SwapOutcome) {
+        self.total += 1;
+        match outcome {
+            SwapOutcome::Success => self.success += 1,
+            SwapOutcome::Failed { .. } => self.failed += 1,
+            SwapOutcome::TimedOut { .. } => self.timed_out += 1,
+            SwapOutcome::Unfinished => self.unfinished += 1,
+        }
+        self.fail_rate = (self.failed + self.timed_out) as f64 / self.total as f64;
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SwapOutcomesStats {
+    /// The stats per `MAKER_COIN/TAKER_COIN` pair
+    pairs: HashMap<String, SwapOutcomesAggregate>,
+    maker_pubkeys: HashMap<String, SwapOutcomesAggregate>,
+    /// The failed and timed out swaps from the oldest to the newest
+    failures: Vec<SwapOutcomeRecord>,
+}
+
+impl SwapOutcomesStats {
+    fn from_log(log: &SwapOutcomesLog) -> SwapOutcomesStats {
+        let mut pairs: HashMap<String, SwapOutcomesAggregate> = HashMap::new();
+        let mut maker_pubkeys: HashMap<String, SwapOutcomesAggregate> = HashMap::new();
+        let mut failures = Vec::new();
+        for record in log.records.iter() {
+            let pair = format!("{}/{}", record.maker_coin, record.taker_coin);
+            pairs.entry(pair).or_default().add(&record.outcome);
+            maker_pubkeys
+                .entry(record.maker_pubkey.clone())
+                .or_default()
+                .add(&record.outcome);
+            if let SwapOutcome::Failed { .. } | SwapOutcome::TimedOut { .. } = record.outcome {
+                failures.push(record.clone());
+            }
+        }
+
+        SwapOutcomesStats {
+            pairs,
+            maker_pubkeys,
+            failures,
+        }
+    }
+}
+
+pub async fn record_swap_outcome(
+    ctx: &MmArc,
+    uuid: Uuid,
+    maker_coin: String,
+    taker_coin: String,
+    maker_pubkey: String,
+    taker_pubkey: String,
+    outcome: SwapOutcome,
+) {
+    let ordermatch_ctx = match OrdermatchContext::from_ctx(ctx) {
+        Ok(c) => c,
+        Err(e) => {
+            log::error!("Error {} on swap {} outcome record", e, uuid);
+            return;
+        },
+    };
+    let record = SwapOutcomeRecord {
+        uuid,
+        maker_coin,
+        taker_coin,
+        maker_pubkey,
+        taker_pubkey,
+        outcome,
+        recorded_at: now_ms() / 1000,
+    };
+    ordermatch_ctx.swap_outcomes.lock().await.record(record);
+}
+
+pub async fn swap_outcomes_stats_rpc(ctx: MmArc, _req: Json) -> Result<Response<Vec<u8>>, String> {
+    let ordermatch_ctx = try_s!(OrdermatchContext::from_ctx(&ctx));
+    let stats = SwapOutcomesStats::from_log(&*ordermatch_ctx.swap_outcomes.lock().await);
+    let res = json!({ "result": stats });
+    Response::builder()
+        .body(json::to_vec(&res).expect("Serialization failed"))
+        .map_err(|e| ERRL!("{}", e))
+}
+
+#[cfg(test)]
+mod swap_outcomes_tests {
+    use super::*;
+
+    fn record(maker_coin: &str, taker_coin: &str, maker_pubkey: &str, outcome: SwapOutcome) -> SwapOutcomeRecord {
+        SwapOutcomeRecord {
+            uuid: Uuid::new_v4(),
+            maker_coin: maker_coin.into(),
+            taker_coin: taker_coin.into(),
+            maker_pubkey: maker_pubkey.into(),
+            taker_pubkey: "taker".into(),
+            outcome,
+            recorded_at: now_ms() / 1000,
+        }
+    }
+
+    #[test]
+    fn test_swap_outcome_from_saved_swap() {
+        let started = r#"{"type":"Started","data":{"lock_duration":7800,"maker_amount":"1","maker_coin":"RICK","maker_coin_start_block":1,"maker_payment_confirmations":1,"maker_payment_lock":1563759539,"my_persistent_pub":"031bb83b58ec130e28e0a6d5d2acf2eb01b0d3f1670e021d47d31db8a858219da8","secret":"0000000000000000000000000000000000000000000000000000000000000000","started_at":1563743939,"taker":"101ace6b08605b9424b0582b5cce044b70a3c8d8d10cb2965e039b0967ae92b9","taker_amount":"1","taker_coin":"MORTY","taker_coin_start_block":1,"taker_payment_confirmations":1,"uuid":"3447b727-fe93-4357-8e5a-8cf2699b7e86"}}"#;
+        let saved_swap = |events: &[&str]| -> SavedSwap {
+            let events: Vec<String> = events
+                .iter()
+                .enumerate()
+                .map(|(i, event)| format!(r#"{{"event":{},"timestamp":{}}}"#, event, i))
+                .collect();
+            let json = format!(
+                r#"{{"type":"Maker","uuid":"3447b727-fe93-4357-8e5a-8cf2699b7e86","events":[{}],"success_events":[],"error_events":[]}}"#,
+                events.join(",")
+            );
+            json::from_str(&json).unwrap()
+        };
+        let finished = r#"{"type":"Finished"}"#;
+
+        let swap = saved_swap(&[started]);
+        assert_eq!(SwapOutcome::from_saved_swap(&swap), SwapOutcome::Unfinished);
+
+        let wait_confirm_failed = r#"{"type":"TakerPaymentWaitConfirmFailed","data":{"error":"Waited too long"}}"#;
+        let swap = saved_swap(&[started, wait_confirm_failed, finished]);
+        let expected = SwapOutcome::TimedOut {
+            event: "TakerPaymentWaitConfirmFailed".into(),
+            error: "Waited too long".into(),
+        };
+        assert_eq!(SwapOutcome::from_saved_swap(&swap), expected);
+
+        // the error text doesn't matter, the outcome depends on the event type only
+        let negotiate_failed = r#"{"type":"NegotiateFailed","data":{"error":"Timeout 90 > 60"}}"#;
+        let swap = saved_swap(&[started, negotiate_failed, finished]);
+        let expected = SwapOutcome::Failed {
+            event: "NegotiateFailed".into(),
+            error: "Timeout 90 > 60".into(),
+        };
+        assert_eq!(SwapOutcome::from_saved_swap(&swap), expected);
+
+        let fee_failed = r#"{"type":"TakerFeeValidateFailed","data":{"error":"Invalid fee"}}"#;
+        let swap = saved_swap(&[started, fee_failed, finished]);
+        let expected = SwapOutcome::Failed {
+            event: "TakerFeeValidateFailed".into(),
+            error: "Invalid fee".into(),
+        };
+        assert_eq!(SwapOutcome::from_saved_swap(&swap), expected);
+
+        let swap = saved_swap(&[started, finished]);
+        assert_eq!(SwapOutcome::from_saved_swap(&swap), SwapOutcome::Success);
+    }
+
+    #[test]
+    fn test_swap_outcomes_stats() {
+        let failed = SwapOutcome::Failed {
+            event: "NegotiateFailed".into(),
+            error: "error".into(),
+        };
+        let mut log = SwapOutcomesLog::default();
+        log.record(record("RICK", "MORTY", "maker1", SwapOutcome::Success));
+        log.record(record("RICK", "MORTY", "maker1", failed.clone()));
+        log.record(record("RICK", "MORTY", "maker2", SwapOutcome::Success));
+        log.record(record("MORTY", "RICK", "maker2", SwapOutcome::Unfinished));
+
+        let stats = SwapOutcomesStats::from_log(&log);
+        let expected = SwapOutcomesAggregate {
+            total: 3,
+            success: 2,
+            failed: 1,
+            timed_out: 0,
+            unfinished: 0,
+            fail_rate: 1. / 3.,
+        };
+        assert_eq!(stats.pairs["RICK/MORTY"], expected);
+
+        let expected = SwapOutcomesAggregate {
+            total: 1,
+            unfinished: 1,
+            ..Default::default()
+        };
+        assert_eq!(stats.pairs["MORTY/RICK"], expected);
+
+        let expected = SwapOutcomesAggregate {
+            total: 2,
+            success: 1,
+            failed: 1,
+            fail_rate: 0.5,
+            ..Default::default()
+        };
+        assert_eq!(stats.maker_pubkeys["maker1"], expected);
+        assert_eq!(stats.maker_pubkeys["maker2"].total, 2);
+        assert_eq!(stats.failures.len(), 1);
+        assert_eq!(stats.failures[0].outcome, failed);
+
+        for _ in 0..MAX_SWAP_OUTCOMES {
+            log.record(record("RICK", "MORTY", "maker1", SwapOutcome::Success));
+        }
+        assert_eq!(log.records.len(), MAX_SWAP_OUTCOMES);
+        assert!(SwapOutcomesStats::from_log(&log).failures.is_empty());
+    }
+}
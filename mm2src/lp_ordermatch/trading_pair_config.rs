@@ -0,0 +1,189 @@
+use super::{canonical_ticker, OrdermatchContext, PairFilter};
+use crate::mm2::lp_swap::calc_max_maker_vol;
+use coins::{lp_coinfind, FeeApproxStage, MmCoinEnum};
+use common::log;
+use common::mm_ctx::MmArc;
+use common::mm_number::MmNumber;
+use futures::compat::Future01CompatExt;
+use http::Response;
+use serde_json::{self as json, Value as Json};
+
+construct_detailed!(DetailedMinTradingVol, min_trading_vol);
+construct_detailed!(DetailedPairMinBaseVol, min_base_vol);
+construct_detailed!(DetailedPairMaxBaseVol, max_base_vol);
+
+#[derive(Deserialize)]
+struct TradingPairConfigReq {
+    base: String,
+    rel: String,
+    /// The price of the order to be built, the `min_base_vol` depends on it
+    price: Option<MmNumber>,
+}
+
+#[derive(Debug, Serialize)]
+struct CoinTradingConfig {
+    coin: String,
+    #[serde(flatten)]
+    min_trading_vol: DetailedMinTradingVol,
+    required_confirmations: u64,
+    requires_notarization: bool,
+    wallet_only: bool,
+}
+
+impl CoinTradingConfig {
+    fn new(ctx: &MmArc, coin: &MmCoinEnum) -> CoinTradingConfig {
+        CoinTradingConfig {
+            coin: coin.ticker().to_owned(),
+            min_trading_vol: coin.min_trading_vol().into(),
+            required_confirmations: coin.required_confirmations(),
+            requires_notarization: coin.requires_notarization(),
+            wallet_only: coin.wallet_only(ctx),
+        }
+    }
+}
+
+/// The constraints that the order builders and the matching apply to the `base`/`rel` pair.
+#[derive(Debug, Serialize)]
+struct TradingPairConfig {
+    base: CoinTradingConfig,
+    rel: CoinTradingConfig,
+    /// The minimum `base` volume of an order at the requested price, see `validate_and_get_min_vol`
+    #[serde(flatten)]
+    min_base_vol: Option<DetailedPairMinBaseVol>,
+    /// The maximum `base` volume of a maker order that our balance allows
+    #[serde(flatten)]
+    max_base_vol: DetailedPairMaxBaseVol,
+    /// Whether our node can match the orders of the pair
+    matchable: bool,
+    /// The reason why the pair is not matchable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    not_matchable_reason: Option<String>,
+}
+
+impl TradingPairConfig {
+    fn new(
+        ctx: &MmArc,
+        base_coin: &MmCoinEnum,
+        rel_coin: &MmCoinEnum,
+        price: Option<MmNumber>,
+        pair_filter: &PairFilter,
+        max_base_vol: MmNumber,
+    ) -> TradingPairConfig {
+        let base = CoinTradingConfig::new(ctx, base_coin);
+        let rel = CoinTradingConfig::new(ctx, rel_coin);
+
+        let min_base_vol = price.filter(|price| *price > MmNumber::from(0)).map(|price| {
+            let base_min_by_rel = rel_coin.min_trading_vol() / price;
+            base_coin.min_trading_vol().max(base_min_by_rel).into()
+        });
+
+        let not_matchable_reason = if base.coin == rel.coin {
+            Some("Base and rel must be different coins".to_owned())
+        } else if base.wallet_only || rel.wallet_only {
+            Some("Wallet only coins can't be traded".to_owned())
+        } else if !pair_filter.is_allowed(&base.coin, &rel.coin) {
+            Some("The pair is denied by the pair filter".to_owned())
+        } else {
+            None
+        };
+
+        TradingPairConfig {
+            base,
+            rel,
+            min_base_vol,
+            max_base_vol: max_base_vol.into(),
+            matchable: not_matchable_reason.is_none(),
+            not_matchable_reason,
+        }
+    }
+}
+
+pub async fn trading_pair_config_rpc(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, String> {
+    let mut req: TradingPairConfigReq = try_s!(json::from_value(req));
+    req.base = canonical_ticker(&ctx, &req.base);
+    req.rel = canonical_ticker(&ctx, &req.rel);
+    let base_coin = match lp_coinfind(&ctx, &req.base).await {
+        Ok(Some(coin)) => coin,
+        Ok(None) => return ERR!("Base coin {} is not found or inactive", req.base),
+        Err(e) => return ERR!("!lp_coinfind({}): {}", req.base, e),
+    };
+    let rel_coin = match lp_coinfind(&ctx, &req.rel).await {
+        Ok(Some(coin)) => coin,
+        Ok(None) => return ERR!("Rel coin {} is not found or inactive", req.rel),
+        Err(e) => return ERR!("!lp_coinfind({}): {}", req.rel, e),
+    };
+
+    let balance = try_s!(base_coin.my_spendable_balance().compat().await);
+    let max_base_vol = match calc_max_maker_vol(&ctx, &base_coin, &balance, FeeApproxStage::OrderIssue).await {
+        Ok(vol) => vol,
+        Err(e) if e.get_inner().not_sufficient_balance() => {
+            log::debug!("{}", e);
+            MmNumber::from(0)
+        },
+        Err(e) => return ERR!("{}", e),
+    };
+
+    let ordermatch_ctx = try_s!(OrdermatchContext::from_ctx(&ctx));
+    let pair_filter = ordermatch_ctx.pair_filter.lock().await.clone();
+    let config = TradingPairConfig::new(&ctx, &base_coin, &rel_coin, req.price, &pair_filter, max_base_vol);
+    let res = json!({ "result": config });
+    Response::builder()
+        .body(json::to_vec(&res).expect("Serialization failed"))
+        .map_err(|e| ERRL!("{}", e))
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod trading_pair_config_tests {
+    use super::*;
+    use coins::{MarketCoinOps, MmCoin, TestCoin};
+    use common::mm_ctx::MmCtxBuilder;
+    use mocktopus::mocking::*;
+    use num_rational::BigRational;
+
+    #[test]
+    fn test_trading_pair_config() {
+        let ctx = MmCtxBuilder::default()
+            .with_conf(json!({
+                "coins": [{"coin": "WALLET", "wallet_only": true}],
+                "pair_filter": {"denylist": [{"base": "MORTY", "rel": "RICK"}]},
+            }))
+            .into_mm_arc();
+        let pair_filter = PairFilter::from_conf(&ctx.conf).unwrap();
+        TestCoin::required_confirmations.mock_safe(|_| MockResult::Return(2));
+        TestCoin::requires_notarization.mock_safe(|_| MockResult::Return(false));
+        TestCoin::min_trading_vol.mock_safe(|coin| {
+            let vol = if coin.ticker() == "RICK" { "0.1" } else { "1" };
+            MockResult::Return(MmNumber::from(vol))
+        });
+        let rick: MmCoinEnum = TestCoin::new("RICK").into();
+        let morty: MmCoinEnum = TestCoin::new("MORTY").into();
+
+        let config = TradingPairConfig::new(&ctx, &rick, &morty, Some(5.into()), &pair_filter, 10.into());
+        assert_eq!(config.base.coin, "RICK");
+        assert_eq!(
+            config.base.min_trading_vol.as_ratio(),
+            &BigRational::new(1.into(), 10.into())
+        );
+        assert_eq!(config.base.required_confirmations, 2);
+        assert!(!config.base.requires_notarization);
+        // 1 MORTY at the price 5 is 0.2 RICK which is greater than the RICK min trading volume
+        let min_base_vol = config.min_base_vol.unwrap();
+        assert_eq!(min_base_vol.as_ratio(), &BigRational::new(1.into(), 5.into()));
+        assert_eq!(config.max_base_vol.as_ratio(), &BigRational::from_integer(10.into()));
+        assert!(config.matchable);
+        assert_eq!(config.not_matchable_reason, None);
+
+        let config = TradingPairConfig::new(&ctx, &morty, &rick, None, &pair_filter, 0.into());
+        assert!(config.min_base_vol.is_none());
+        assert!(!config.matchable);
+        assert_eq!(
+            config.not_matchable_reason,
+            Some("The pair is denied by the pair filter".to_owned())
+        );
+
+        let wallet: MmCoinEnum = TestCoin::new("WALLET").into();
+        let config = TradingPairConfig::new(&ctx, &rick, &wallet, None, &pair_filter, 0.into());
+        assert!(config.rel.wallet_only);
+        assert!(!config.matchable);
+    }
+}
@@ -0,0 +1,36 @@
+use super::{canonical_ticker, resync_orderbook};
+use coins::is_wallet_only_ticker;
+use common::mm_ctx::MmArc;
+use http::Response;
+use serde_json::{self as json, Value as Json};
+
+#[derive(Deserialize)]
+struct UpdateOrderbookReq {
+    base: String,
+    rel: String,
+}
+
+/// Forces the resync of the `base`/`rel` orderbook, e.g. if it became stale after the network issues.
+pub async fn update_orderbook_rpc(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, String> {
+    let mut req: UpdateOrderbookReq = try_s!(json::from_value(req));
+    req.base = canonical_ticker(&ctx, &req.base);
+    req.rel = canonical_ticker(&ctx, &req.rel);
+    if req.base == req.rel {
+        return ERR!("Base and rel must be different coins");
+    }
+    if is_wallet_only_ticker(&ctx, &req.base) || is_wallet_only_ticker(&ctx, &req.rel) {
+        return ERR!("Pair {}/{} has wallet only coins", req.base, req.rel);
+    }
+
+    let orders = try_s!(resync_orderbook(&ctx, &req.base, &req.rel).await);
+    let res = json!({
+        "result": {
+            "base": req.base,
+            "rel": req.rel,
+            "orders": orders,
+        }
+    });
+    Response::builder()
+        .body(json::to_vec(&res).expect("Serialization failed"))
+        .map_err(|e| ERRL!("{}", e))
+}
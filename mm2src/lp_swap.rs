@@ -412,7 +412,7 @@ pub fn active_swaps(ctx: &MmArc) -> Result<Vec<Uuid>, String> {
     Ok(uuids)
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SwapConfirmationsSettings {
     pub maker_coin_confs: u64,
     pub maker_coin_nota: bool,
@@ -442,6 +442,22 @@ pub enum AtomicLocktimeVersion {
     },
 }
 
+impl AtomicLocktimeVersion {
+    pub fn saved_version(&self) -> SavedLocktimeVersion {
+        match self {
+            AtomicLocktimeVersion::V1 => SavedLocktimeVersion::V1,
+            AtomicLocktimeVersion::V2 { .. } => SavedLocktimeVersion::V2,
+        }
+    }
+}
+
+/// The atomic locktime version stored in the swap data to let the user know how the `lock_duration` was calculated.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub enum SavedLocktimeVersion {
+    V1,
+    V2,
+}
+
 pub fn lp_atomic_locktime_v1(maker_coin: &str, taker_coin: &str) -> u64 {
     if maker_coin == "BTC" || taker_coin == "BTC" {
         PAYMENT_LOCKTIME * 10
@@ -677,10 +693,15 @@ pub struct MySwapInfo {
     my_amount: BigDecimal,
     other_amount: BigDecimal,
     pub started_at: u64,
+    #[serde(default)]
+    pub lock_duration: u64,
+    /// Is None for the swaps started before the locktime version was saved.
+    #[serde(default)]
+    pub lock_time_version: Option<SavedLocktimeVersion>,
 }
 
 impl SavedSwap {
-    fn is_finished(&self) -> bool {
+    pub fn is_finished(&self) -> bool {
         match self {
             SavedSwap::Maker(swap) => swap.is_finished(),
             SavedSwap::Taker(swap) => swap.is_finished(),
@@ -715,6 +736,20 @@ impl SavedSwap {
         }
     }
 
+    pub fn first_error_event(&self) -> Option<Json> {
+        match self {
+            SavedSwap::Maker(swap) => swap.first_error_event(),
+            SavedSwap::Taker(swap) => swap.first_error_event(),
+        }
+    }
+
+    pub fn is_timed_out(&self) -> bool {
+        match self {
+            SavedSwap::Maker(swap) => swap.is_timed_out(),
+            SavedSwap::Taker(swap) => swap.is_timed_out(),
+        }
+    }
+
     fn recover_funds(self, ctx: MmArc) -> Result<RecoveredSwap, String> {
         let maker_ticker = try_s!(self.maker_coin_ticker());
         // Should remove `block_on` when recover_funds is async.
@@ -821,6 +856,15 @@ impl<'a> From<&'a SavedSwap> for MySwapStatusResponse<'a> {
     }
 }
 
+/// Loads the saved swap performed on `my` node from its file.
+pub fn load_my_swap(ctx: &MmArc, uuid: &Uuid) -> Result<SavedSwap, String> {
+    let content = try_s!(slurp(&my_swap_file_path(ctx, uuid)));
+    if content.is_empty() {
+        return ERR!("Swap {} data is not found", uuid);
+    }
+    Ok(try_s!(json::from_slice(&content)))
+}
+
 /// Returns the status of swap performed on `my` node
 pub fn my_swap_status(ctx: MmArc, req: Json) -> HyRes {
     let uuid: Uuid = try_h!(json::from_value(req["params"]["uuid"].clone()));
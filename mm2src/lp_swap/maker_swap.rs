@@ -5,8 +5,8 @@ use super::trade_preimage::{TradePreimageRequest, TradePreimageRpcError, TradePr
 use super::{broadcast_my_swap_status, broadcast_swap_message_every, check_other_coin_balance_for_swap,
             dex_fee_amount_from_taker_coin, get_locked_amount, my_swap_file_path, my_swaps_dir, recv_swap_msg,
             swap_topic, AtomicSwap, LockedAmount, MySwapInfo, NegotiationDataMsg, NegotiationDataV2, RecoveredSwap,
-            RecoveredSwapAction, SavedSwap, SavedTradeFee, SwapConfirmationsSettings, SwapError, SwapMsg,
-            SwapsContext, TransactionIdentifier, WAIT_CONFIRM_INTERVAL};
+            RecoveredSwapAction, SavedLocktimeVersion, SavedSwap, SavedTradeFee, SwapConfirmationsSettings, SwapError,
+            SwapMsg, SwapsContext, TransactionIdentifier, WAIT_CONFIRM_INTERVAL};
 
 use crate::mm2::lp_network::subscribe_to_topic;
 use crate::mm2::lp_ordermatch::{MakerOrderBuilder, OrderConfirmationsSettings};
@@ -24,7 +24,7 @@ use parking_lot::Mutex as PaMutex;
 use primitives::hash::H264;
 use rand::Rng;
 use rpc::v1::types::{Bytes as BytesJson, H160 as H160Json, H256 as H256Json, H264 as H264Json};
-use serde_json as json;
+use serde_json::{self as json, Value as Json};
 use std::path::PathBuf;
 use std::sync::{atomic::Ordering, Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
 use uuid::Uuid;
@@ -110,6 +110,8 @@ pub struct MakerSwapData {
     secret_hash: Option<H160Json>,
     my_persistent_pub: H264Json,
     lock_duration: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lock_time_version: Option<SavedLocktimeVersion>,
     pub maker_amount: BigDecimal,
     pub taker_amount: BigDecimal,
     maker_payment_confirmations: u64,
@@ -161,6 +163,7 @@ pub struct MakerSwap {
     mutable: RwLock<MakerSwapMut>,
     conf_settings: SwapConfirmationsSettings,
     payment_locktime: u64,
+    lock_time_version: Option<SavedLocktimeVersion>,
 }
 
 impl MakerSwap {
@@ -257,6 +260,7 @@ impl MakerSwap {
         maker_coin: MmCoinEnum,
         taker_coin: MmCoinEnum,
         payment_locktime: u64,
+        lock_time_version: Option<SavedLocktimeVersion>,
     ) -> Self {
         MakerSwap {
             ctx,
@@ -274,6 +278,7 @@ impl MakerSwap {
             taker_payment_confirmed: Atomic::new(false),
             conf_settings,
             payment_locktime,
+            lock_time_version,
             mutable: RwLock::new(MakerSwapMut {
                 data: MakerSwapData::default(),
                 other_persistent_pub: H264::default(),
@@ -363,6 +368,7 @@ impl MakerSwap {
             secret: secret.into(),
             started_at,
             lock_duration: self.payment_locktime,
+            lock_time_version: self.lock_time_version,
             maker_amount: self.maker_amount.clone(),
             taker_amount: self.taker_amount.clone(),
             maker_payment_confirmations: self.conf_settings.maker_coin_confs,
@@ -925,6 +931,7 @@ impl MakerSwap {
             maker_coin,
             taker_coin,
             data.lock_duration,
+            data.lock_time_version,
         );
         let command = saved.events.last().unwrap().get_command();
         for saved_event in saved.events {
@@ -1193,6 +1200,16 @@ impl MakerSwapEvent {
         )
     }
 
+    /// Whether the event is an error of waiting for a transaction that was not confirmed in time.
+    fn is_timeout(&self) -> bool {
+        matches!(
+            self,
+            MakerSwapEvent::MakerPaymentWaitConfirmFailed(_)
+                | MakerSwapEvent::TakerPaymentWaitConfirmFailed(_)
+                | MakerSwapEvent::TakerPaymentSpendConfirmFailed(_)
+        )
+    }
+
     fn is_success(&self) -> bool {
         matches!(
             self,
@@ -1303,6 +1320,8 @@ impl MakerSavedSwap {
                     my_amount: data.maker_amount.clone(),
                     other_amount: data.taker_amount.clone(),
                     started_at: data.started_at,
+                    lock_duration: data.lock_duration,
+                    lock_time_version: data.lock_time_version,
                 }),
                 _ => None,
             },
@@ -1370,6 +1389,22 @@ impl MakerSavedSwap {
 
         Ok(true)
     }
+
+    /// Returns the first error event serialized to JSON.
+    pub fn first_error_event(&self) -> Option<Json> {
+        self.events
+            .iter()
+            .find(|event| event.event.is_error())
+            .map(|event| json::to_value(&event.event).expect("Serialization failed"))
+    }
+
+    /// Whether the first error event is a timeout.
+    pub fn is_timed_out(&self) -> bool {
+        self.events
+            .iter()
+            .find(|event| event.event.is_error())
+            .map_or(false, |event| event.event.is_timeout())
+    }
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -2100,6 +2135,27 @@ mod maker_swap_tests {
         );
     }
 
+    #[test]
+    fn test_maker_saved_swap_lock_time_version() {
+        // the swaps saved before the locktime version was introduced should be deserialized with None
+        let maker_saved_json = r#"{"error_events":["StartFailed","NegotiateFailed","TakerFeeValidateFailed","MakerPaymentTransactionFailed","MakerPaymentDataSendFailed","TakerPaymentValidateFailed","TakerPaymentSpendFailed","TakerPaymentSpendConfirmFailed","MakerPaymentRefunded","MakerPaymentRefundFailed"],"events":[{"event":{"data":{"lock_duration":7800,"maker_amount":"3.54932734","maker_coin":"KMD","maker_coin_start_block":1452970,"maker_payment_confirmations":1,"maker_payment_lock":1563759539,"my_persistent_pub":"031bb83b58ec130e28e0a6d5d2acf2eb01b0d3f1670e021d47d31db8a858219da8","secret":"0000000000000000000000000000000000000000000000000000000000000000","started_at":1563743939,"taker":"101ace6b08605b9424b0582b5cce044b70a3c8d8d10cb2965e039b0967ae92b9","taker_amount":"0.02004833998671660000000000","taker_coin":"ETH","taker_coin_start_block":8196380,"taker_payment_confirmations":1,"uuid":"3447b727-fe93-4357-8e5a-8cf2699b7e86"},"type":"Started"},"timestamp":1563743939211},{"event":{"data":{"taker_payment_locktime":1563751737,"taker_pubkey":"03101ace6b08605b9424b0582b5cce044b70a3c8d8d10cb2965e039b0967ae92b9"},"type":"Negotiated"},"timestamp":1563743979835},{"event":{"data":{"tx_hash":"a59203eb2328827de00bed699a29389792906e4f39fdea145eb40dc6b3821bd6","tx_hex":"f8690284ee6b280082520894d8997941dd1346e9231118d5685d866294f59e5b865af3107a4000801ca0743d2b7c9fad65805d882179062012261be328d7628ae12ee08eff8d7657d993a07eecbd051f49d35279416778faa4664962726d516ce65e18755c9b9406a9c2fd"},"type":"TakerFeeValidated"},"timestamp":1563744052878}],"success_events":["Started","Negotiated","TakerFeeValidated","MakerPaymentSent","TakerPaymentReceived","TakerPaymentWaitConfirmStarted","TakerPaymentValidatedAndConfirmed","TakerPaymentSpent","TakerPaymentSpendConfirmStarted","TakerPaymentSpendConfirmed","Finished"],"uuid":"3447b727-fe93-4357-8e5a-8cf2699b7e86"}"#;
+        let mut maker_saved_swap: MakerSavedSwap = json::from_str(maker_saved_json).unwrap();
+        let my_info = maker_saved_swap.get_my_info().unwrap();
+        assert_eq!(my_info.lock_time_version, None);
+
+        if let MakerSwapEvent::Started(ref mut data) = maker_saved_swap.events[0].event {
+            data.lock_time_version = Some(SavedLocktimeVersion::V2);
+        }
+        let serialized = json::to_string(&maker_saved_swap).unwrap();
+        let deserialized: MakerSavedSwap = json::from_str(&serialized).unwrap();
+        let my_info = deserialized.get_my_info().unwrap();
+        assert_eq!(my_info.lock_time_version, Some(SavedLocktimeVersion::V2));
+        assert_eq!(
+            my_info.lock_duration,
+            maker_saved_swap.get_my_info().unwrap().lock_duration
+        );
+    }
+
     #[test]
     fn test_maker_swap_event_should_ban() {
         let event = MakerSwapEvent::TakerPaymentWaitConfirmFailed("err".into());
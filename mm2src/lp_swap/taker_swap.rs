@@ -5,7 +5,7 @@ use super::trade_preimage::{TradePreimageRequest, TradePreimageRpcError, TradePr
 use super::{broadcast_my_swap_status, broadcast_swap_message_every, check_other_coin_balance_for_swap,
             dex_fee_amount_from_taker_coin, dex_fee_rate, dex_fee_threshold, get_locked_amount, my_swap_file_path,
             my_swaps_dir, recv_swap_msg, swap_topic, AtomicSwap, LockedAmount, MySwapInfo, NegotiationDataMsg,
-            NegotiationDataV2, RecoveredSwap, RecoveredSwapAction, SavedSwap, SavedTradeFee,
+            NegotiationDataV2, RecoveredSwap, RecoveredSwapAction, SavedLocktimeVersion, SavedSwap, SavedTradeFee,
             SwapConfirmationsSettings, SwapError, SwapMsg, SwapsContext, TransactionIdentifier, WAIT_CONFIRM_INTERVAL};
 use crate::mm2::lp_network::subscribe_to_topic;
 use crate::mm2::lp_ordermatch::{MatchBy, OrderConfirmationsSettings, TakerAction, TakerOrderBuilder};
@@ -182,6 +182,8 @@ impl TakerSavedSwap {
                     my_amount: data.taker_amount.clone(),
                     other_amount: data.maker_amount.clone(),
                     started_at: data.started_at,
+                    lock_duration: data.lock_duration,
+                    lock_time_version: data.lock_time_version,
                 }),
                 _ => None,
             },
@@ -243,6 +245,22 @@ impl TakerSavedSwap {
 
         Ok(true)
     }
+
+    /// Returns the first error event serialized to JSON.
+    pub fn first_error_event(&self) -> Option<Json> {
+        self.events
+            .iter()
+            .find(|event| event.event.is_error())
+            .map(|event| json::to_value(&event.event).expect("Serialization failed"))
+    }
+
+    /// Whether the first error event is a timeout.
+    pub fn is_timed_out(&self) -> bool {
+        self.events
+            .iter()
+            .find(|event| event.event.is_error())
+            .map_or(false, |event| event.event.is_timeout())
+    }
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -394,6 +412,8 @@ pub struct TakerSwapData {
     maker: H256Json,
     my_persistent_pub: H264Json,
     lock_duration: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lock_time_version: Option<SavedLocktimeVersion>,
     pub maker_amount: BigDecimal,
     pub taker_amount: BigDecimal,
     maker_payment_confirmations: u64,
@@ -452,6 +472,7 @@ pub struct TakerSwap {
     mutable: RwLock<TakerSwapMut>,
     conf_settings: SwapConfirmationsSettings,
     payment_locktime: u64,
+    lock_time_version: Option<SavedLocktimeVersion>,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -540,6 +561,16 @@ impl TakerSwapEvent {
         )
     }
 
+    /// Whether the event is an error of waiting for a transaction that was not confirmed or spent in time.
+    fn is_timeout(&self) -> bool {
+        matches!(
+            self,
+            TakerSwapEvent::MakerPaymentWaitConfirmFailed(_)
+                | TakerSwapEvent::TakerPaymentWaitConfirmFailed(_)
+                | TakerSwapEvent::TakerPaymentWaitForSpendFailed(_)
+        )
+    }
+
     fn is_success(&self) -> bool {
         matches!(
             self,
@@ -657,6 +688,7 @@ impl TakerSwap {
         maker_coin: MmCoinEnum,
         taker_coin: MmCoinEnum,
         payment_locktime: u64,
+        lock_time_version: Option<SavedLocktimeVersion>,
     ) -> Self {
         TakerSwap {
             ctx,
@@ -674,6 +706,7 @@ impl TakerSwap {
             errors: PaMutex::new(Vec::new()),
             conf_settings,
             payment_locktime,
+            lock_time_version,
             mutable: RwLock::new(TakerSwapMut {
                 data: TakerSwapData::default(),
                 other_persistent_pub: H264::default(),
@@ -775,6 +808,7 @@ impl TakerSwap {
             maker: self.maker.bytes.into(),
             started_at,
             lock_duration: self.payment_locktime,
+            lock_time_version: self.lock_time_version,
             maker_amount: self.maker_amount.to_decimal(),
             taker_amount: self.taker_amount.to_decimal(),
             maker_payment_confirmations: self.conf_settings.maker_coin_confs,
@@ -1297,6 +1331,7 @@ impl TakerSwap {
             maker_coin,
             taker_coin,
             data.lock_duration,
+            data.lock_time_version,
         );
         let command = saved.events.last().unwrap().get_command();
         for saved_event in saved.events {
@@ -6796,6 +6796,7 @@ mod wasm_bindgen_tests {
             eth_taker,
             jst_taker,
             PAYMENT_LOCKTIME,
+            None,
         );
 
         let maker_swap = MakerSwap::new(
@@ -6810,6 +6811,7 @@ mod wasm_bindgen_tests {
             eth_maker,
             jst_maker,
             PAYMENT_LOCKTIME,
+            None,
         );
 
         let taker_swap_fut = run_taker_swap(RunTakerSwapInput::StartNew(taker_swap), ctx_taker);
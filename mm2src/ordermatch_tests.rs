@@ -8,7 +8,7 @@ use common::{block_on,
              mm_ctx::{MmArc, MmCtx, MmCtxBuilder},
              privkey::key_pair_from_seed};
 use futures::{channel::mpsc, lock::Mutex as AsyncMutex, StreamExt};
-use mm2_libp2p::atomicdex_behaviour::AdexBehaviourCmd;
+use mm2_libp2p::atomicdex_behaviour::{AdexBehaviourCmd, AdexResponse};
 use mm2_libp2p::{decode_message, PeerId};
 use mocktopus::mocking::*;
 use rand::{seq::SliceRandom, thread_rng, Rng};
@@ -985,7 +985,7 @@ fn test_choose_maker_confs_settings() {
     let taker_order = TakerOrderBuilder::new(&coin, &coin).build_unchecked();
     TestCoin::requires_notarization.mock_safe(|_| MockResult::Return(true));
     TestCoin::required_confirmations.mock_safe(|_| MockResult::Return(8));
-    let settings = choose_maker_confs_and_notas(None, &taker_order.request, &coin, &coin);
+    let settings = choose_maker_confs_and_notas(None, &taker_order.request, &coin, &coin, None);
     // should pick settings from coin configuration
     assert!(settings.maker_coin_nota);
     assert_eq!(settings.maker_coin_confs, 8);
@@ -1000,7 +1000,7 @@ fn test_choose_maker_confs_settings() {
     };
     // no confs set
     let taker_order = TakerOrderBuilder::new(&coin, &coin).build_unchecked();
-    let settings = choose_maker_confs_and_notas(Some(maker_conf_settings), &taker_order.request, &coin, &coin);
+    let settings = choose_maker_confs_and_notas(Some(maker_conf_settings), &taker_order.request, &coin, &coin, None);
     // should pick settings from maker order
     assert!(!settings.maker_coin_nota);
     assert_eq!(settings.maker_coin_confs, 1);
@@ -1022,7 +1022,7 @@ fn test_choose_maker_confs_settings() {
     let taker_order = TakerOrderBuilder::new(&coin, &coin)
         .with_conf_settings(taker_conf_settings)
         .build_unchecked();
-    let settings = choose_maker_confs_and_notas(Some(maker_conf_settings), &taker_order.request, &coin, &coin);
+    let settings = choose_maker_confs_and_notas(Some(maker_conf_settings), &taker_order.request, &coin, &coin, None);
     // should pick settings from taker request because taker will wait less time for our
     // payment confirmation
     assert!(!settings.maker_coin_nota);
@@ -1045,7 +1045,7 @@ fn test_choose_maker_confs_settings() {
     let taker_order = TakerOrderBuilder::new(&coin, &coin)
         .with_conf_settings(taker_conf_settings)
         .build_unchecked();
-    let settings = choose_maker_confs_and_notas(Some(maker_conf_settings), &taker_order.request, &coin, &coin);
+    let settings = choose_maker_confs_and_notas(Some(maker_conf_settings), &taker_order.request, &coin, &coin, None);
     // keep using our settings allowing taker to wait for our payment conf as much as he likes
     assert!(!settings.maker_coin_nota);
     assert_eq!(settings.maker_coin_confs, 10);
@@ -1068,7 +1068,7 @@ fn test_choose_maker_confs_settings() {
     let taker_order = TakerOrderBuilder::new(&coin, &coin)
         .with_conf_settings(taker_conf_settings)
         .build_unchecked();
-    let settings = choose_maker_confs_and_notas(Some(maker_conf_settings), &taker_order.request, &coin, &coin);
+    let settings = choose_maker_confs_and_notas(Some(maker_conf_settings), &taker_order.request, &coin, &coin, None);
 
     // Taker conf settings should not have any effect on maker conf requirements for taker payment
     assert!(settings.taker_coin_nota);
@@ -1091,7 +1091,7 @@ fn test_choose_maker_confs_settings() {
         .with_conf_settings(taker_conf_settings)
         .with_action(TakerAction::Sell)
         .build_unchecked();
-    let settings = choose_maker_confs_and_notas(Some(maker_conf_settings), &taker_order.request, &coin, &coin);
+    let settings = choose_maker_confs_and_notas(Some(maker_conf_settings), &taker_order.request, &coin, &coin, None);
     // should pick settings from taker request because taker will wait less time for our
     // payment confirmation
     assert!(!settings.maker_coin_nota);
@@ -1100,6 +1100,83 @@ fn test_choose_maker_confs_settings() {
     assert_eq!(settings.taker_coin_confs, 1);
 }
 
+#[test]
+fn test_choose_maker_confs_settings_policy() {
+    let coin = TestCoin::default().into();
+    TestCoin::requires_notarization.mock_safe(|_| MockResult::Return(false));
+    TestCoin::required_confirmations.mock_safe(|_| MockResult::Return(1));
+    let policy = MakerConfsPolicy {
+        min_confs: 3,
+        requires_nota: true,
+    };
+
+    // taker omitted the settings, no maker policy
+    let taker_order = TakerOrderBuilder::new(&coin, &coin).build_unchecked();
+    let settings = choose_maker_confs_and_notas(None, &taker_order.request, &coin, &coin, None);
+    assert!(!settings.taker_coin_nota);
+    assert_eq!(settings.taker_coin_confs, 1);
+
+    // taker omitted the settings, the maker policy is applied
+    let settings = choose_maker_confs_and_notas(None, &taker_order.request, &coin, &coin, Some(policy));
+    assert!(settings.taker_coin_nota);
+    assert_eq!(settings.taker_coin_confs, 3);
+    assert!(!settings.maker_coin_nota);
+    assert_eq!(settings.maker_coin_confs, 1);
+
+    // the policy doesn't lower the maker order settings
+    let maker_conf_settings = OrderConfirmationsSettings {
+        base_confs: 1,
+        base_nota: false,
+        rel_confs: 5,
+        rel_nota: false,
+    };
+    let settings = choose_maker_confs_and_notas(
+        Some(maker_conf_settings),
+        &taker_order.request,
+        &coin,
+        &coin,
+        Some(policy),
+    );
+    assert_eq!(settings.taker_coin_confs, 5);
+
+    // taker set the settings, the notarization is not overridden as it affects the lock time
+    let taker_conf_settings = OrderConfirmationsSettings {
+        base_confs: 1,
+        base_nota: false,
+        rel_confs: 1,
+        rel_nota: false,
+    };
+    let taker_order = TakerOrderBuilder::new(&coin, &coin)
+        .with_conf_settings(taker_conf_settings)
+        .build_unchecked();
+    let settings = choose_maker_confs_and_notas(None, &taker_order.request, &coin, &coin, Some(policy));
+    assert!(!settings.taker_coin_nota);
+    assert_eq!(settings.taker_coin_confs, 3);
+}
+
+#[test]
+fn test_maker_confs_policy_from_conf() {
+    let ctx = MmCtxBuilder::default()
+        .with_conf(json!({
+            "maker_confs_policy": {
+                "RICK": {"min_confs": 2, "requires_nota": true},
+                "MORTY": {"min_confs": 3},
+            }
+        }))
+        .into_mm_arc();
+    let expected = MakerConfsPolicy {
+        min_confs: 2,
+        requires_nota: true,
+    };
+    assert_eq!(maker_confs_policy(&ctx, "RICK").unwrap(), Some(expected));
+    let expected = MakerConfsPolicy {
+        min_confs: 3,
+        requires_nota: false,
+    };
+    assert_eq!(maker_confs_policy(&ctx, "MORTY").unwrap(), Some(expected));
+    assert_eq!(maker_confs_policy(&ctx, "ETH").unwrap(), None);
+}
+
 #[test]
 fn test_choose_taker_confs_settings_buy_action() {
     let coin = TestCoin::default().into();
@@ -1549,7 +1626,10 @@ fn test_request_and_fill_orderbook() {
                 (pubkey, item)
             })
             .collect();
-        let orderbook = GetOrderbookRes { pubkey_orders: result };
+        let orderbook = GetOrderbookRes {
+            pubkey_orders: result,
+            orderbook_item_version: ORDERBOOK_ITEM_VERSION,
+        };
         let encoded = encode_message(&orderbook).unwrap();
 
         // send the response through the response channel
@@ -2215,6 +2295,7 @@ fn test_trie_diff_avoid_cycle_on_insertion() {
             delta: vec![],
             next_root: [2; 8],
         }))),
+        insertion_order: VecDeque::from(vec![[1; 8]]),
     };
 
     assert_eq!(expected, history);
@@ -2385,3 +2466,1618 @@ fn test_remove_and_purge_pubkey_pair_orders() {
     remove_and_purge_pubkey_pair_orders(&mut orderbook, &pubkey, &rick_morty_pair);
     check_if_orderbook_contains_only(&orderbook, &pubkey, &rick_kmd_orders);
 }
+
+#[test]
+fn test_process_maker_order_created_uuid_of_another_pubkey() {
+    let (ctx, pubkey, secret) = make_ctx_for_tests();
+    let (other_pubkey, _other_secret) = pubkey_and_secret_for_test("other passphrase");
+    let orders = make_random_orders(pubkey.clone(), &secret, "RICK".into(), "MORTY".into(), 1);
+    block_on(insert_or_update_order(&ctx, orders[0].clone()));
+
+    // other pubkey tries to announce an order with the same uuid
+    let created_msg = new_protocol::MakerOrderCreated {
+        uuid: orders[0].uuid.into(),
+        base: "RICK".into(),
+        rel: "MORTY".into(),
+        price: BigRational::from_integer(2.into()),
+        max_volume: BigRational::from_integer(10.into()),
+        min_volume: BigRational::from_integer(0.into()),
+        conf_settings: OrderConfirmationsSettings::default(),
+        created_at: now_ms() / 1000,
+        timestamp: now_ms() / 1000,
+        pair_trie_root: H64::default(),
+    };
+    assert!(!block_on(process_maker_order_created(
+        ctx.clone(),
+        other_pubkey.clone(),
+        created_msg
+    )));
+
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+    let orderbook = block_on(ordermatch_ctx.orderbook.lock());
+    let order = orderbook.find_order_by_uuid(&orders[0].uuid).unwrap();
+    assert_eq!(order, orders[0]);
+    assert!(!orderbook.pubkeys_state.contains_key(&other_pubkey));
+    check_if_orderbook_contains_only(&orderbook, &pubkey, &orders);
+}
+
+fn maker_order_created_for_test(uuid: Uuid) -> new_protocol::MakerOrderCreated {
+    new_protocol::MakerOrderCreated {
+        uuid: uuid.into(),
+        base: "RICK".into(),
+        rel: "MORTY".into(),
+        price: BigRational::from_integer(1.into()),
+        max_volume: BigRational::from_integer(10.into()),
+        min_volume: BigRational::from_integer(0.into()),
+        conf_settings: OrderConfirmationsSettings::default(),
+        created_at: now_ms() / 1000,
+        timestamp: now_ms() / 1000,
+        pair_trie_root: H64::default(),
+    }
+}
+
+/// Creates the `base`/`rel` order of the `pubkey` with a random uuid, see [`maker_order_created_for_test`].
+pub(super) fn orderbook_item_for_test(pubkey: &str, base: &str, rel: &str) -> OrderbookItem {
+    let mut created = maker_order_created_for_test(Uuid::new_v4());
+    created.base = base.into();
+    created.rel = rel.into();
+    (created, pubkey.to_owned()).into()
+}
+
+#[test]
+fn test_process_maker_order_updated_before_created() {
+    let (ctx, pubkey, _secret) = make_ctx_for_tests();
+    let (other_pubkey, _other_secret) = pubkey_and_secret_for_test("other passphrase");
+    let uuid = Uuid::new_v4();
+
+    let mut update = new_protocol::MakerOrderUpdated::new(uuid);
+    update.with_new_price(BigRational::from_integer(2.into()));
+    assert!(!block_on(process_maker_order_updated(
+        ctx.clone(),
+        pubkey.clone(),
+        update
+    )));
+
+    // the update of another pubkey must not be applied
+    let mut update = new_protocol::MakerOrderUpdated::new(uuid);
+    update.with_new_price(BigRational::from_integer(5.into()));
+    assert!(!block_on(process_maker_order_updated(
+        ctx.clone(),
+        other_pubkey,
+        update
+    )));
+
+    let mut update = new_protocol::MakerOrderUpdated::new(uuid);
+    update.with_new_max_volume(BigRational::from_integer(3.into()));
+    assert!(!block_on(process_maker_order_updated(
+        ctx.clone(),
+        pubkey.clone(),
+        update
+    )));
+
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+    assert!(block_on(ordermatch_ctx.orderbook.lock())
+        .find_order_by_uuid(&uuid)
+        .is_none());
+
+    let created_msg = maker_order_created_for_test(uuid);
+    assert!(block_on(process_maker_order_created(
+        ctx.clone(),
+        pubkey.clone(),
+        created_msg
+    )));
+
+    let orderbook = block_on(ordermatch_ctx.orderbook.lock());
+    let order = orderbook.find_order_by_uuid(&uuid).unwrap();
+    assert_eq!(order.pubkey, pubkey);
+    assert_eq!(order.price, BigRational::from_integer(2.into()));
+    assert_eq!(order.max_volume, BigRational::from_integer(3.into()));
+    assert!(orderbook.pending_updates.is_empty());
+}
+
+#[test]
+fn test_pending_order_updates_expired() {
+    let (ctx, pubkey, _secret) = make_ctx_for_tests();
+    let uuid = Uuid::new_v4();
+
+    let mut update = new_protocol::MakerOrderUpdated::new(uuid);
+    update.with_new_price(BigRational::from_integer(2.into()));
+    assert!(!block_on(process_maker_order_updated(
+        ctx.clone(),
+        pubkey.clone(),
+        update
+    )));
+
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+    {
+        let mut orderbook = block_on(ordermatch_ctx.orderbook.lock());
+        let pending = orderbook.pending_updates.get_mut(&uuid).unwrap();
+        pending.received_at -= PENDING_UPDATES_TTL;
+    }
+
+    let created_msg = maker_order_created_for_test(uuid);
+    assert!(block_on(process_maker_order_created(ctx.clone(), pubkey, created_msg)));
+
+    let orderbook = block_on(ordermatch_ctx.orderbook.lock());
+    let order = orderbook.find_order_by_uuid(&uuid).unwrap();
+    assert_eq!(order.price, BigRational::from_integer(1.into()));
+    assert!(orderbook.pending_updates.is_empty());
+}
+
+#[test]
+fn test_pending_order_updates_limit() {
+    let mut orderbook = Orderbook::default();
+    let (pubkey, _secret) = pubkey_and_secret_for_test("passphrase");
+    let uuid = Uuid::new_v4();
+    for i in 0..MAX_PENDING_UPDATES_PER_ORDER + 5 {
+        let mut update = new_protocol::MakerOrderUpdated::new(uuid);
+        update.with_new_price(BigRational::from_integer(i.into()));
+        orderbook.add_pending_update(&pubkey, update);
+    }
+    assert_eq!(
+        orderbook.pending_updates[&uuid].updates.len(),
+        MAX_PENDING_UPDATES_PER_ORDER
+    );
+
+    for _ in 0..MAX_PENDING_UPDATES_ORDERS + 5 {
+        orderbook.add_pending_update(&pubkey, new_protocol::MakerOrderUpdated::new(Uuid::new_v4()));
+    }
+    assert_eq!(orderbook.pending_updates.len(), MAX_PENDING_UPDATES_ORDERS);
+}
+
+#[test]
+fn test_maker_order_to_orderbook_item() {
+    let (pubkey, _secret) = pubkey_and_secret_for_test("passphrase");
+    let mut maker = MakerOrder {
+        base: "RICK".into(),
+        rel: "MORTY".into(),
+        created_at: now_ms(),
+        updated_at: Some(now_ms()),
+        max_base_vol: 10.into(),
+        min_base_vol: 1.into(),
+        price: "0.5".into(),
+        matches: HashMap::new(),
+        started_swaps: Vec::new(),
+        uuid: Uuid::new_v4(),
+        conf_settings: Some(OrderConfirmationsSettings::default()),
+        changes_history: None,
+    };
+    maker.matches.insert(Uuid::new_v4(), MakerMatch {
+        request: TakerRequest {
+            base: "MORTY".into(),
+            rel: "RICK".into(),
+            base_amount: 1.into(),
+            rel_amount: 2.into(),
+            action: TakerAction::Sell,
+            uuid: Uuid::new_v4(),
+            sender_pubkey: H256Json::default(),
+            dest_pub_key: H256Json::default(),
+            match_by: MatchBy::Any,
+            conf_settings: None,
+        },
+        reserved: MakerReserved {
+            base: "RICK".into(),
+            rel: "MORTY".into(),
+            base_amount: 2.into(),
+            rel_amount: 1.into(),
+            taker_order_uuid: Uuid::new_v4(),
+            maker_order_uuid: maker.uuid,
+            sender_pubkey: H256Json::default(),
+            dest_pub_key: H256Json::default(),
+            conf_settings: None,
+        },
+        connect: None,
+        connected: None,
+        last_updated: now_ms(),
+    });
+
+    let item = maker.to_orderbook_item(pubkey.clone());
+    assert_eq!(item.max_volume, BigRational::from_integer(8.into()));
+    assert_eq!(item.min_volume, BigRational::from_integer(1.into()));
+
+    let message = item.to_maker_order_created(maker.conf_settings.unwrap());
+    let reconstructed: OrderbookItem = (message, pubkey).into();
+    assert_eq!(reconstructed, item);
+}
+
+#[test]
+fn test_orderbook_sync_relays() {
+    let mut orderbook = Orderbook {
+        min_sync_relays: 3,
+        ..Default::default()
+    };
+    let topic = orderbook_topic_from_base_rel("RICK", "MORTY");
+    assert_eq!(orderbook.sync_confidence(&topic), None);
+
+    // only 2 of 4 requested relays responded
+    assert!(!orderbook.record_sync_relays(&topic, vec!["relay1".into(), "relay2".into()], 4));
+    assert_eq!(orderbook.sync_confidence(&topic), Some(0.5));
+
+    // the same relay responded again, it must not be counted twice
+    assert!(!orderbook.record_sync_relays(&topic, vec!["relay1".into()], 4));
+    assert_eq!(orderbook.sync_confidence(&topic), Some(0.5));
+
+    assert!(orderbook.record_sync_relays(&topic, vec!["relay3".into(), "relay4".into()], 4));
+    assert_eq!(orderbook.sync_confidence(&topic), Some(1.));
+
+    // the number of the responded relays is less than the configured minimum even if all requested relays responded
+    let topic = orderbook_topic_from_base_rel("RICK", "ETH");
+    assert!(!orderbook.record_sync_relays(&topic, vec!["relay1".into()], 1));
+    assert_eq!(orderbook.sync_relays[&topic].expected, 3);
+}
+
+#[test]
+fn test_orderbook_sync_relays_reset_on_resubscribe() {
+    let mut orderbook = Orderbook {
+        min_sync_relays: 2,
+        ..Default::default()
+    };
+    let topic = orderbook_topic_from_base_rel("RICK", "MORTY");
+    let now = now_ms() / 1000;
+    orderbook
+        .topics_subscribed_to
+        .insert(topic.clone(), OrderbookRequestingState::NotRequested {
+            subscribed_at: now,
+        });
+
+    assert!(orderbook.try_start_sync_request(&topic, now));
+    assert!(!orderbook.record_sync_relays(&topic, vec!["relay1".into()], 2));
+    // the request is not repeated until the retry interval passes
+    assert!(!orderbook.try_start_sync_request(&topic, now + ORDERBOOK_SYNC_RETRY_INTERVAL - 1));
+    assert!(orderbook.try_start_sync_request(&topic, now + ORDERBOOK_SYNC_RETRY_INTERVAL));
+
+    // the relays responded before the reconnection must confirm the orderbook again
+    orderbook.reset_requested_topics(now);
+    assert_eq!(orderbook.sync_confidence(&topic), None);
+    assert!(orderbook.try_start_sync_request(&topic, now));
+    assert!(!orderbook.record_sync_relays(&topic, vec!["relay2".into()], 2));
+}
+
+#[test]
+fn test_subscribe_to_orderbook_topic_min_sync_relays() {
+    let (ctx, _pubkey, _secret) = make_ctx_for_tests();
+    let (_cmd_tx, mut cmd_rx) = p2p_context_mock();
+
+    let topic = orderbook_topic_from_base_rel("RICK", "MORTY");
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+    {
+        let mut orderbook = block_on(ordermatch_ctx.orderbook.lock());
+        orderbook.min_sync_relays = 2;
+        // the orderbook would be considered filled by the keep alive messages if only one relay was required
+        let subscribed_at = now_ms() / 1000 - ORDERBOOK_REQUESTING_TIMEOUT - 1;
+        orderbook
+            .topics_subscribed_to
+            .insert(topic.clone(), OrderbookRequestingState::NotRequested { subscribed_at });
+    }
+
+    let requests_num = Arc::new(Mutex::new(0));
+    let requests_num_clone = requests_num.clone();
+    spawn(async move {
+        loop {
+            match cmd_rx.next().await.unwrap() {
+                AdexBehaviourCmd::RequestRelays { response_tx, .. } => {
+                    *requests_num_clone.lock().unwrap() += 1;
+                    let response = GetOrderbookRes {
+                        pubkey_orders: HashMap::new(),
+                        orderbook_item_version: ORDERBOOK_ITEM_VERSION,
+                    };
+                    let response = AdexResponse::Ok {
+                        response: encode_message(&response).unwrap(),
+                    };
+                    // only one of two relays responded
+                    response_tx
+                        .send(vec![
+                            (PeerId::random(), response),
+                            (PeerId::random(), AdexResponse::None),
+                        ])
+                        .unwrap();
+                },
+                _ => panic!("Unexpected cmd"),
+            }
+        }
+    });
+
+    block_on(subscribe_to_orderbook_topic(&ctx, "RICK", "MORTY", true)).unwrap();
+    assert_eq!(*requests_num.lock().unwrap(), 1);
+    // the orderbook is not synced, but it's not requested again within the retry interval
+    block_on(subscribe_to_orderbook_topic(&ctx, "RICK", "MORTY", true)).unwrap();
+    assert_eq!(*requests_num.lock().unwrap(), 1);
+
+    let orderbook = block_on(ordermatch_ctx.orderbook.lock());
+    assert_ne!(
+        orderbook.topics_subscribed_to.get(&topic),
+        Some(&OrderbookRequestingState::Requested)
+    );
+    assert_eq!(orderbook.sync_confidence(&topic), Some(0.5));
+}
+
+#[test]
+fn test_orderbook_taker_liquidity() {
+    let (pubkey1, _) = pubkey_and_secret_for_test("passphrase1");
+    let (pubkey2, _) = pubkey_and_secret_for_test("passphrase2");
+    let order =
+        |pubkey: &str, base: &str, rel: &str, price: BigRational, max_volume: i64, min_volume: i64| OrderbookItem {
+            pubkey: pubkey.to_owned(),
+            base: base.to_owned(),
+            rel: rel.to_owned(),
+            price,
+            max_volume: BigRational::from_integer(max_volume.into()),
+            min_volume: BigRational::from_integer(min_volume.into()),
+            uuid: Uuid::new_v4(),
+            created_at: now_ms() / 1000,
+        };
+
+    let mut orderbook = Orderbook::default();
+    // asks
+    orderbook.insert_or_update_order_update_trie(order(
+        &pubkey1,
+        "RICK",
+        "MORTY",
+        BigRational::from_integer(1.into()),
+        10,
+        1,
+    ));
+    orderbook.insert_or_update_order_update_trie(order(
+        &pubkey2,
+        "RICK",
+        "MORTY",
+        BigRational::from_integer(2.into()),
+        5,
+        0,
+    ));
+    // the bid to buy 5 RICK for 10 MORTY at 2 MORTY per RICK
+    orderbook.insert_or_update_order_update_trie(order(
+        &pubkey1,
+        "MORTY",
+        "RICK",
+        BigRational::new(1.into(), 2.into()),
+        10,
+        2,
+    ));
+
+    let liquidity = |action: TakerAction, price: MmNumber, volume: i32, match_by: MatchBy| {
+        orderbook.taker_liquidity("RICK", "MORTY", &action, &price, &volume.into(), &match_by)
+    };
+    let expected = |matchable: bool, depth: i32| TakerLiquidity {
+        matchable,
+        depth: depth.into(),
+    };
+
+    assert_eq!(
+        liquidity(TakerAction::Buy, "1.5".into(), 3, MatchBy::Any),
+        expected(true, 10)
+    );
+    // there is enough depth at this price, but the request can be matched with one order only
+    assert_eq!(
+        liquidity(TakerAction::Buy, 2.into(), 20, MatchBy::Any),
+        expected(false, 15)
+    );
+    assert_eq!(
+        liquidity(TakerAction::Buy, "0.5".into(), 3, MatchBy::Any),
+        expected(false, 0)
+    );
+
+    let pubkey2_bytes = hex::decode(&pubkey2).unwrap();
+    let match_by = MatchBy::Pubkeys(std::iter::once(pubkey2_bytes[1..].into()).collect());
+    assert_eq!(liquidity(TakerAction::Buy, 2.into(), 3, match_by), expected(true, 5));
+    let match_by = MatchBy::Orders(std::iter::once(Uuid::new_v4()).collect());
+    assert_eq!(liquidity(TakerAction::Buy, 2.into(), 3, match_by), expected(false, 0));
+
+    assert_eq!(
+        liquidity(TakerAction::Sell, 2.into(), 3, MatchBy::Any),
+        expected(true, 5)
+    );
+    // 0 MORTY is less than the min volume of the bid
+    assert_eq!(
+        liquidity(TakerAction::Sell, 2.into(), 0, MatchBy::Any),
+        expected(false, 5)
+    );
+    assert_eq!(
+        liquidity(TakerAction::Sell, 3.into(), 3, MatchBy::Any),
+        expected(false, 0)
+    );
+}
+
+#[test]
+fn test_relays_connectivity() {
+    let mut connectivity = RelaysConnectivity::default();
+    let mut now = RELAYS_CONNECTIVITY_CHECK_INTERVAL;
+    assert!(connectivity.check_due(now));
+    assert!(!connectivity.on_checked(now, 2));
+    assert!(!connectivity.check_due(now + RELAYS_CONNECTIVITY_CHECK_INTERVAL - 1));
+
+    // a short gap in the mesh is not considered a disconnection
+    for _ in 1..RELAYS_DISCONNECTED_CHECKS {
+        now += RELAYS_CONNECTIVITY_CHECK_INTERVAL;
+        assert!(!connectivity.on_checked(now, 0));
+    }
+    now += RELAYS_CONNECTIVITY_CHECK_INTERVAL;
+    assert!(!connectivity.on_checked(now, 1));
+    assert!(!connectivity.disconnected);
+
+    for _ in 0..RELAYS_DISCONNECTED_CHECKS {
+        now += RELAYS_CONNECTIVITY_CHECK_INTERVAL;
+        assert!(!connectivity.on_checked(now, 0));
+    }
+    assert!(connectivity.disconnected);
+    now += RELAYS_CONNECTIVITY_CHECK_INTERVAL;
+    assert!(connectivity.on_checked(now, 1));
+    // the resubscription is triggered once
+    now += RELAYS_CONNECTIVITY_CHECK_INTERVAL;
+    assert!(!connectivity.on_checked(now, 1));
+}
+
+#[test]
+fn test_resubscribe_to_orderbook_topics() {
+    let (ctx, _pubkey, _secret) = make_ctx_for_tests();
+    let (cmd_tx, mut cmd_rx) = mpsc::channel(10);
+    // the orderbook is requested by a spawned future, so P2PContext can't be mocked
+    P2PContext::new(cmd_tx).store_to_mm_arc(&ctx);
+
+    let requested_topic = orderbook_topic_from_base_rel("RICK", "MORTY");
+    let not_requested_topic = orderbook_topic_from_base_rel("RICK", "ETH");
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+    {
+        let mut orderbook = block_on(ordermatch_ctx.orderbook.lock());
+        orderbook
+            .topics_subscribed_to
+            .insert(requested_topic.clone(), OrderbookRequestingState::Requested);
+        orderbook
+            .topics_subscribed_to
+            .insert(not_requested_topic.clone(), OrderbookRequestingState::NotRequested {
+                subscribed_at: now_ms() / 1000,
+            });
+    }
+
+    // the pubsub subscriptions are considered lost after the node reconnects to the relays
+    block_on(resubscribe_to_orderbook_topics(&ctx));
+
+    let mut resubscribed = HashSet::new();
+    let mut orderbook_requested = false;
+    block_on(async {
+        while resubscribed.len() < 2 || !orderbook_requested {
+            match cmd_rx.next().await.unwrap() {
+                AdexBehaviourCmd::Subscribe { topic } => {
+                    resubscribed.insert(topic);
+                },
+                // the response channel is dropped, so the request fails and the orderbook stays not requested
+                AdexBehaviourCmd::RequestAnyRelay { req, .. } => {
+                    let expected = P2PRequest::Ordermatch(OrdermatchRequest::GetOrderbook {
+                        base: "MORTY".into(),
+                        rel: "RICK".into(),
+                    });
+                    assert_eq!(decode_message::<P2PRequest>(&req).unwrap(), expected);
+                    orderbook_requested = true;
+                },
+                _ => panic!("Unexpected cmd"),
+            }
+        }
+    });
+
+    let expected: HashSet<_> = vec![requested_topic.clone(), not_requested_topic].into_iter().collect();
+    assert_eq!(resubscribed, expected);
+    let orderbook = block_on(ordermatch_ctx.orderbook.lock());
+    assert_ne!(
+        orderbook.topics_subscribed_to[&requested_topic],
+        OrderbookRequestingState::Requested
+    );
+}
+
+#[test]
+fn test_orderbook_max_orders_per_pubkey() {
+    let ctx = MmCtxBuilder::default()
+        .with_conf(json!({"max_orders_per_pubkey": 3}))
+        .with_secp256k1_key_pair(key_pair_from_seed("my passphrase").unwrap())
+        .into_mm_arc();
+    let (pubkey, secret) = pubkey_and_secret_for_test("passphrase");
+    let mut orders = make_random_orders(pubkey.clone(), &secret, "RICK".into(), "MORTY".into(), 5);
+    for (i, order) in orders.iter_mut().enumerate() {
+        order.created_at = 10 + i as u64;
+    }
+    // the last order is older than all the others
+    orders[4].created_at = 1;
+
+    for order in orders[..4].iter() {
+        block_on(insert_or_update_order(&ctx, order.clone()));
+    }
+
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+    let mut orderbook = block_on(ordermatch_ctx.orderbook.lock());
+    // the oldest order is evicted to insert the new one
+    assert!(orderbook.find_order_by_uuid(&orders[0].uuid).is_none());
+    assert_eq!(orderbook.evicted_orders, 1);
+    check_if_orderbook_contains_only(&orderbook, &pubkey, &orders[1..4].to_vec());
+
+    // the new order older than all the known ones is evicted immediately
+    orderbook.insert_or_update_order_update_trie(orders[4].clone());
+    assert!(orderbook.find_order_by_uuid(&orders[4].uuid).is_none());
+    assert_eq!(orderbook.evicted_orders, 2);
+    check_if_orderbook_contains_only(&orderbook, &pubkey, &orders[1..4].to_vec());
+
+    // reprice of the existing order is accepted
+    let mut repriced = orders[2].clone();
+    repriced.price = BigRational::from_integer(5.into());
+    orderbook.insert_or_update_order_update_trie(repriced.clone());
+    assert_eq!(orderbook.evicted_orders, 2);
+    check_if_orderbook_contains_only(&orderbook, &pubkey, &vec![
+        orders[1].clone(),
+        repriced,
+        orders[3].clone(),
+    ]);
+
+    // the roots of the pubkey at the limit are still synced
+    let alb_pair = alb_ordered_pair("RICK", "MORTY");
+    orderbook.topics_subscribed_to.insert(
+        orderbook_topic_from_ordered_pair(&alb_pair),
+        OrderbookRequestingState::Requested,
+    );
+    let keep_alive = new_protocol::PubkeyKeepAlive {
+        trie_roots: iter::once((alb_pair.clone(), [1; 8])).collect(),
+        timestamp: now_ms() / 1000,
+    };
+    let expected_roots: HashMap<_, _> = iter::once((alb_pair, [1; 8])).collect();
+    match orderbook.process_keep_alive(&pubkey, keep_alive, false) {
+        Some(OrdermatchRequest::SyncPubkeyOrderbookState {
+            pubkey: requested,
+            trie_roots,
+        }) => {
+            assert_eq!(requested, pubkey);
+            assert_eq!(trie_roots, expected_roots);
+        },
+        other => panic!("Expected the sync request, got {:?}", other),
+    }
+
+    // our own orders are never limited
+    let my_pubkey = hex::encode(&**ctx.secp256k1_key_pair().public());
+    let my_secret = (&*ctx.secp256k1_key_pair().private().secret).clone();
+    let my_orders = make_random_orders(my_pubkey.clone(), &my_secret, "RICK".into(), "MORTY".into(), 5);
+    for order in my_orders.iter() {
+        orderbook.insert_or_update_order_update_trie(order.clone());
+    }
+    assert_eq!(orderbook.evicted_orders, 2);
+    assert_eq!(orderbook.pubkeys_state[&my_pubkey].orders_uuids.len(), 5);
+    for order in my_orders {
+        assert_eq!(orderbook.find_order_by_uuid(&order.uuid), Some(order));
+    }
+}
+
+#[test]
+fn test_orderbook_max_orders_per_pubkey_disabled() {
+    let ctx = MmCtxBuilder::default()
+        .with_conf(json!({"max_orders_per_pubkey": 0}))
+        .into_mm_arc();
+    let (pubkey, secret) = pubkey_and_secret_for_test("passphrase");
+    let orders = make_random_orders(pubkey.clone(), &secret, "RICK".into(), "MORTY".into(), 5);
+    for order in orders.iter() {
+        block_on(insert_or_update_order(&ctx, order.clone()));
+    }
+
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+    let orderbook = block_on(ordermatch_ctx.orderbook.lock());
+    assert_eq!(orderbook.max_orders_per_pubkey, None);
+    assert_eq!(orderbook.evicted_orders, 0);
+    check_if_orderbook_contains_only(&orderbook, &pubkey, &orders);
+}
+
+#[test]
+fn test_trie_diff_history_is_bounded() {
+    const UPDATES_NUMBER: usize = TRIE_DIFF_HISTORY_MAX_LEN * 3;
+
+    let (ctx, pubkey, secret) = make_ctx_for_tests();
+    let mut order = make_random_orders(pubkey.clone(), &secret, "C1".into(), "C2".into(), 1)
+        .pop()
+        .expect("Expected one order");
+    // the initial price should never be repeated by the updates below
+    order.price = BigRational::from_integer(1000000.into());
+    block_on(insert_or_update_order(&ctx, order.clone()));
+
+    let alb_ordered_pair = alb_ordered_pair("C1", "C2");
+    let initial_root = pair_trie_root_by_pub(&ctx, &pubkey, &alb_ordered_pair);
+
+    let mut recent_root = initial_root;
+    for i in 1..=UPDATES_NUMBER {
+        // each update changes the price so every trie root is unique
+        order.price = BigRational::new(i.into(), 1000.into());
+        block_on(insert_or_update_order(&ctx, order.clone()));
+        if i == UPDATES_NUMBER - 10 {
+            recent_root = pair_trie_root_by_pub(&ctx, &pubkey, &alb_ordered_pair);
+        }
+    }
+
+    {
+        let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+        let orderbook = block_on(ordermatch_ctx.orderbook.lock());
+        let history = orderbook
+            .pubkeys_state
+            .get(&pubkey)
+            .unwrap()
+            .order_pairs_trie_state_history
+            .get(&alb_ordered_pair)
+            .unwrap();
+        assert_eq!(history.inner.len(), TRIE_DIFF_HISTORY_MAX_LEN);
+        assert_eq!(history.insertion_order.len(), TRIE_DIFF_HISTORY_MAX_LEN);
+        assert!(!history.inner.contains_key(&initial_root));
+    }
+
+    // the diff starting from the initial root has been evicted, the full trie is expected
+    let mut result = block_on(process_sync_pubkey_orderbook_state(
+        ctx.clone(),
+        pubkey.clone(),
+        HashMap::from_iter(iter::once((alb_ordered_pair.clone(), initial_root))),
+    ))
+    .unwrap()
+    .unwrap();
+    match result.pair_orders_diff.remove(&alb_ordered_pair).unwrap() {
+        DeltaOrFullTrie::FullTrie(trie) => assert_eq!(trie, vec![(order.uuid, order.clone())]),
+        DeltaOrFullTrie::Delta(_) => panic!("Must be DeltaOrFullTrie::FullTrie"),
+    }
+
+    // the recent diffs are still available
+    let mut result = block_on(process_sync_pubkey_orderbook_state(
+        ctx.clone(),
+        pubkey.clone(),
+        HashMap::from_iter(iter::once((alb_ordered_pair.clone(), recent_root))),
+    ))
+    .unwrap()
+    .unwrap();
+    match result.pair_orders_diff.remove(&alb_ordered_pair).unwrap() {
+        DeltaOrFullTrie::Delta(delta) => assert_eq!(delta.get(&order.uuid), Some(&Some(order))),
+        DeltaOrFullTrie::FullTrie(_) => panic!("Must be DeltaOrFullTrie::Delta"),
+    }
+}
+
+#[test]
+fn test_orderbook_response_version_compatibility() {
+    /// The orderbook response of the peers that don't send the orders version
+    #[derive(Debug, Deserialize, Serialize)]
+    struct LegacyGetOrderbookRes {
+        pubkey_orders: HashMap<String, GetOrderbookPubkeyItem>,
+    }
+
+    let (ctx, _, _) = make_ctx_for_tests();
+    let (_, mut cmd_rx) = p2p_context_mock();
+    let (pubkey, secret) = pubkey_and_secret_for_test("passphrase");
+    let orders: Vec<_> = make_random_orders(pubkey.clone(), &secret, "RICK".into(), "MORTY".into(), 2)
+        .into_iter()
+        .map(|order| (order.uuid, order))
+        .collect();
+    let pubkey_orders = |orders: PubkeyOrders| {
+        let item = GetOrderbookPubkeyItem {
+            orders,
+            last_keep_alive: now_ms() / 1000,
+            last_signed_pubkey_payload: vec![],
+        };
+        iter::once((pubkey.clone(), item)).collect::<HashMap<_, _>>()
+    };
+
+    // the payload of the legacy peer is decoded as the version 1
+    let legacy_payload = encode_message(&LegacyGetOrderbookRes {
+        pubkey_orders: pubkey_orders(orders.clone()),
+    })
+    .unwrap();
+    let actual: GetOrderbookRes = decode_message(&legacy_payload).unwrap();
+    assert_eq!(actual.orderbook_item_version, 1);
+    assert_eq!(actual.pubkey_orders[&pubkey].orders, orders);
+
+    // the legacy peer is still able to decode our payload
+    let payload = encode_message(&GetOrderbookRes {
+        pubkey_orders: pubkey_orders(orders.clone()),
+        orderbook_item_version: ORDERBOOK_ITEM_VERSION,
+    })
+    .unwrap();
+    let actual: LegacyGetOrderbookRes = decode_message(&payload).unwrap();
+    assert_eq!(actual.pubkey_orders[&pubkey].orders, orders);
+
+    // the orders of the next version are not added to the orderbook
+    let next_version_payload = encode_message(&GetOrderbookRes {
+        pubkey_orders: pubkey_orders(orders),
+        orderbook_item_version: ORDERBOOK_ITEM_VERSION + 1,
+    })
+    .unwrap();
+    spawn(async move {
+        loop {
+            match cmd_rx.next().await.unwrap() {
+                AdexBehaviourCmd::RequestAnyRelay { response_tx, .. } => {
+                    response_tx
+                        .send(Some((PeerId::random(), next_version_payload)))
+                        .unwrap();
+                    break;
+                },
+                _ => continue,
+            }
+        }
+    });
+    block_on(request_and_fill_orderbook(&ctx, "RICK", "MORTY")).unwrap();
+
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+    let orderbook = block_on(ordermatch_ctx.orderbook.lock());
+    assert!(orderbook.order_set.is_empty());
+    assert!(!orderbook.pubkeys_state.contains_key(&pubkey));
+}
+
+#[test]
+fn test_matched_swap_serde() {
+    let matched_swap = MatchedSwap {
+        uuid: new_uuid(),
+        my_order_uuid: new_uuid(),
+        role: MatchedSwapRole::Maker,
+        maker_coin: "RICK".into(),
+        taker_coin: "MORTY".into(),
+        maker_amount: MmNumber::from("0.5"),
+        taker_amount: MmNumber::from("1"),
+        other_pubkey: [1; 32].into(),
+        lock_time: 7800,
+        lock_time_version: SavedLocktimeVersion::V2,
+        conf_settings: SwapConfirmationsSettings {
+            maker_coin_confs: 1,
+            maker_coin_nota: false,
+            taker_coin_confs: 2,
+            taker_coin_nota: true,
+        },
+    };
+
+    let serialized = json::to_vec(&matched_swap).unwrap();
+    let deserialized: MatchedSwap = json::from_slice(&serialized).unwrap();
+    assert_eq!(deserialized, matched_swap);
+}
+
+#[test]
+fn test_set_price_pair_filter() {
+    let conf = json!({
+        "pair_filter": {
+            "denylist": [{"base": "ETH", "rel": "JST"}],
+        }
+    });
+    let ctx = MmCtxBuilder::default().with_conf(conf).into_mm_arc();
+    let req = json!({
+        "base": "ETH",
+        "rel": "JST",
+        "price": "1",
+        "volume": "1",
+    });
+
+    let err = block_on(set_price(ctx.clone(), req.clone())).unwrap_err();
+    assert!(err.contains("The pair ETH/JST is denied by the pair filter"), "{}", err);
+
+    // the allowed pair passes the filter and fails because the coin is not activated
+    let mut other_pair = req.clone();
+    other_pair["rel"] = "RICK".into();
+    let err = block_on(set_price(ctx.clone(), other_pair)).unwrap_err();
+    assert!(err.contains("Base coin ETH is not found"), "{}", err);
+
+    // the filter is replaced by the RPC
+    let filter = json!({
+        "method": "set_pair_filter",
+        "allowlist": [{"base": "ETH", "rel": "JST"}],
+    });
+    block_on(set_pair_filter_rpc(ctx.clone(), filter)).unwrap();
+    let err = block_on(set_price(ctx.clone(), req.clone())).unwrap_err();
+    assert!(err.contains("Base coin ETH is not found"), "{}", err);
+
+    let mut other_pair = req;
+    other_pair["rel"] = "RICK".into();
+    let err = block_on(set_price(ctx, other_pair.clone())).unwrap_err();
+    assert!(
+        err.contains("The pair ETH/RICK is denied by the pair filter"),
+        "{}",
+        err
+    );
+
+    // not configured filter allows any pair
+    let ctx = MmCtxBuilder::default().into_mm_arc();
+    let err = block_on(set_price(ctx, other_pair)).unwrap_err();
+    assert!(err.contains("Base coin ETH is not found"), "{}", err);
+}
+
+#[test]
+fn test_taker_order_into_maker_order_conf_settings() {
+    let conf_settings = OrderConfirmationsSettings {
+        base_confs: 1,
+        base_nota: false,
+        rel_confs: 5,
+        rel_nota: true,
+    };
+    assert_eq!(conf_settings.reversed().reversed(), conf_settings);
+
+    let taker_order = |action: TakerAction| TakerOrder {
+        request: TakerRequest {
+            base: "RICK".into(),
+            rel: "MORTY".into(),
+            uuid: Uuid::new_v4(),
+            dest_pub_key: H256Json::default(),
+            sender_pubkey: H256Json::default(),
+            base_amount: 10.into(),
+            rel_amount: 20.into(),
+            action,
+            match_by: MatchBy::Any,
+            conf_settings: Some(conf_settings),
+        },
+        matches: HashMap::new(),
+        created_at: now_ms(),
+        order_type: OrderType::GoodTillCancelled,
+        min_volume: 1.into(),
+        timeout: 30,
+    };
+
+    // the maker order keeps the pair of the "sell" taker order, so RICK stays the base coin
+    let maker_order: MakerOrder = taker_order(TakerAction::Sell).into();
+    assert_eq!(maker_order.base, "RICK");
+    assert_eq!(maker_order.rel, "MORTY");
+    let maker_conf_settings = maker_order.conf_settings.unwrap();
+    assert_eq!(maker_conf_settings.base_confs, 1);
+    assert!(!maker_conf_settings.base_nota);
+    assert_eq!(maker_conf_settings.rel_confs, 5);
+    assert!(maker_conf_settings.rel_nota);
+
+    // the "buy" taker order is recreated with reversed pair, so the RICK settings have to become the rel ones
+    let maker_order: MakerOrder = taker_order(TakerAction::Buy).into();
+    assert_eq!(maker_order.base, "MORTY");
+    assert_eq!(maker_order.rel, "RICK");
+    let maker_conf_settings = maker_order.conf_settings.unwrap();
+    assert_eq!(maker_conf_settings.base_confs, 5);
+    assert!(maker_conf_settings.base_nota);
+    assert_eq!(maker_conf_settings.rel_confs, 1);
+    assert!(!maker_conf_settings.rel_nota);
+    assert_eq!(maker_conf_settings.reversed(), conf_settings);
+}
+
+fn maker_match_for_test(maker_order_uuid: Uuid, base_amount: MmNumber, rel_amount: MmNumber) -> MakerMatch {
+    let taker_order_uuid = Uuid::new_v4();
+    MakerMatch {
+        request: TakerRequest {
+            base: "MORTY".into(),
+            rel: "RICK".into(),
+            base_amount: rel_amount.clone(),
+            rel_amount: base_amount.clone(),
+            action: TakerAction::Buy,
+            uuid: taker_order_uuid,
+            sender_pubkey: H256Json::default(),
+            dest_pub_key: H256Json::default(),
+            match_by: MatchBy::Any,
+            conf_settings: None,
+        },
+        reserved: MakerReserved {
+            base: "RICK".into(),
+            rel: "MORTY".into(),
+            base_amount,
+            rel_amount,
+            taker_order_uuid,
+            maker_order_uuid,
+            sender_pubkey: H256Json::default(),
+            dest_pub_key: H256Json::default(),
+            conf_settings: None,
+        },
+        connect: None,
+        connected: None,
+        last_updated: now_ms(),
+    }
+}
+
+fn connect_maker_match_for_test(order_match: &mut MakerMatch) {
+    order_match.connect = Some(TakerConnect {
+        taker_order_uuid: order_match.reserved.taker_order_uuid,
+        maker_order_uuid: order_match.reserved.maker_order_uuid,
+        sender_pubkey: H256Json::default(),
+        dest_pub_key: H256Json::default(),
+    });
+    order_match.connected = Some(MakerConnected {
+        taker_order_uuid: order_match.reserved.taker_order_uuid,
+        maker_order_uuid: order_match.reserved.maker_order_uuid,
+        method: "connected".into(),
+        sender_pubkey: H256Json::default(),
+        dest_pub_key: H256Json::default(),
+    });
+}
+
+#[test]
+fn test_maker_order_filled() {
+    let mut maker = MakerOrder {
+        base: "RICK".into(),
+        rel: "MORTY".into(),
+        created_at: now_ms(),
+        updated_at: Some(now_ms()),
+        max_base_vol: 10.into(),
+        min_base_vol: 1.into(),
+        price: 2.into(),
+        matches: HashMap::new(),
+        started_swaps: Vec::new(),
+        uuid: Uuid::new_v4(),
+        conf_settings: None,
+        changes_history: None,
+    };
+    // the order without matches is not filled even if its volume is less than min
+    assert_eq!(maker.filled(), None);
+
+    let mut first_match = maker_match_for_test(maker.uuid, 4.into(), 8.into());
+    connect_maker_match_for_test(&mut first_match);
+    maker.matches.insert(first_match.request.uuid, first_match);
+    assert_eq!(maker.filled(), None);
+
+    // the last match exhausts the available amount, but it's not connected yet
+    let mut last_match = maker_match_for_test(maker.uuid, 6.into(), 12.into());
+    maker.matches.insert(last_match.request.uuid, last_match.clone());
+    assert_eq!(maker.available_amount(), 0.into());
+    assert_eq!(maker.filled(), None);
+
+    connect_maker_match_for_test(&mut last_match);
+    maker.matches.insert(last_match.request.uuid, last_match);
+    let expected = MakerOrderFilled {
+        uuid: maker.uuid,
+        base: "RICK".into(),
+        rel: "MORTY".into(),
+        filled_base_vol: 10.into(),
+        filled_rel_vol: 20.into(),
+        matches_count: 2,
+    };
+    assert_eq!(maker.filled(), Some(expected));
+}
+
+#[test]
+fn test_process_taker_connect_replayed() {
+    let (ctx, _pubkey, _secret) = make_ctx_for_tests();
+    let (cmd_tx, _cmd_rx) = mpsc::channel(10);
+    // the connected message is broadcasted by a spawned future, so P2PContext can't be mocked
+    P2PContext::new(cmd_tx).store_to_mm_arc(&ctx);
+    save_my_maker_order.mock_safe(|_, _| MockResult::Return(()));
+    static mut CONNECT_START_CALLS: usize = 0;
+    lp_connect_start_bob.mock_safe(|_, _, _| {
+        MockResult::Return(unsafe {
+            CONNECT_START_CALLS += 1;
+        })
+    });
+
+    let base_coin = TestCoin::new("RICK").into();
+    let rel_coin = TestCoin::new("MORTY").into();
+    let mut maker = MakerOrderBuilder::new(&base_coin, &rel_coin)
+        .with_max_base_vol(10.into())
+        .with_min_base_vol(Some(1.into()))
+        .with_price(2.into())
+        .build_unchecked();
+    let maker_uuid = maker.uuid;
+    let order_match = maker_match_for_test(maker_uuid, 4.into(), 8.into());
+    let taker_uuid = order_match.request.uuid;
+    maker.matches.insert(taker_uuid, order_match);
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+    block_on(ordermatch_ctx.my_maker_orders.lock()).insert(maker_uuid, maker);
+
+    let connect = TakerConnect {
+        taker_order_uuid: taker_uuid,
+        maker_order_uuid: maker_uuid,
+        sender_pubkey: H256Json::default(),
+        dest_pub_key: H256Json::default(),
+    };
+    block_on(process_taker_connect(ctx.clone(), H256Json::default(), connect.clone()));
+    block_on(process_taker_connect(ctx.clone(), H256Json::default(), connect.clone()));
+    assert_eq!(unsafe { CONNECT_START_CALLS }, 1);
+
+    // the connect is replayed after the match was reset by the repeated request
+    {
+        let mut maker_orders = block_on(ordermatch_ctx.my_maker_orders.lock());
+        let order_match = maker_orders
+            .get_mut(&maker_uuid)
+            .unwrap()
+            .matches
+            .get_mut(&taker_uuid)
+            .unwrap();
+        order_match.connect = None;
+        order_match.connected = None;
+    }
+    block_on(process_taker_connect(ctx, H256Json::default(), connect));
+    assert_eq!(unsafe { CONNECT_START_CALLS }, 1);
+    let maker_orders = block_on(ordermatch_ctx.my_maker_orders.lock());
+    assert_eq!(maker_orders[&maker_uuid].started_swaps, vec![taker_uuid]);
+}
+
+#[test]
+fn test_process_taker_request_declined_over_max_concurrent_swaps() {
+    let ctx = MmCtxBuilder::default()
+        .with_conf(json!({ "max_concurrent_swaps": 1 }))
+        .with_secp256k1_key_pair(key_pair_from_seed("passphrase").unwrap())
+        .into_mm_arc();
+    let base_coin = TestCoin::new("RICK").into();
+    let rel_coin = TestCoin::new("MORTY").into();
+    let mut maker = MakerOrderBuilder::new(&base_coin, &rel_coin)
+        .with_max_base_vol(10.into())
+        .with_min_base_vol(Some(1.into()))
+        .with_price(2.into())
+        .build_unchecked();
+    let maker_uuid = maker.uuid;
+    // the match is reserved, but the taker hasn't connected yet
+    let order_match = maker_match_for_test(maker_uuid, 4.into(), 8.into());
+    maker.matches.insert(order_match.request.uuid, order_match);
+    let mut my_maker_orders = HashMap::new();
+    my_maker_orders.insert(maker_uuid, maker.clone());
+    assert!(concurrent_swaps_limit_reached(&ctx, &my_maker_orders));
+    // the limit is not configured
+    let (no_limit_ctx, _pubkey, _secret) = make_ctx_for_tests();
+    assert!(!concurrent_swaps_limit_reached(&no_limit_ctx, &my_maker_orders));
+
+    // the connected match has started the swap that is not running anymore
+    let order_match = my_maker_orders
+        .get_mut(&maker_uuid)
+        .unwrap()
+        .matches
+        .values_mut()
+        .next()
+        .unwrap();
+    connect_maker_match_for_test(order_match);
+    assert!(!concurrent_swaps_limit_reached(&ctx, &my_maker_orders));
+
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+    block_on(ordermatch_ctx.my_maker_orders.lock()).insert(maker_uuid, maker);
+
+    let request = TakerRequest {
+        base: "RICK".into(),
+        rel: "MORTY".into(),
+        base_amount: 2.into(),
+        rel_amount: 4.into(),
+        action: TakerAction::Buy,
+        uuid: Uuid::new_v4(),
+        sender_pubkey: H256Json::default(),
+        dest_pub_key: H256Json::default(),
+        match_by: MatchBy::Any,
+        conf_settings: None,
+    };
+    let maker_orders = block_on(ordermatch_ctx.my_maker_orders.lock());
+    assert_eq!(
+        maker_orders[&maker_uuid].match_with_request(&request),
+        OrderMatchResult::Matched((2.into(), 4.into()))
+    );
+    drop(maker_orders);
+
+    // the second simultaneous request is declined instead of being reserved
+    block_on(process_taker_request(ctx, H256Json::default(), request));
+    let maker_orders = block_on(ordermatch_ctx.my_maker_orders.lock());
+    assert_eq!(maker_orders[&maker_uuid].matches.len(), 1);
+}
+
+#[test]
+fn test_orderbook_events() {
+    let (ctx, pubkey, secret) = make_ctx_for_tests();
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+    let (mut rick_morty_rx, mut rick_eth_rx, mut all_rx) = {
+        let mut orderbook = block_on(ordermatch_ctx.orderbook.lock());
+        (
+            orderbook.subscribe_events(Some(("RICK".into(), "MORTY".into()))),
+            orderbook.subscribe_events(Some(("RICK".into(), "ETH".into()))),
+            orderbook.subscribe_events(None),
+        )
+    };
+
+    // the bid of the RICK/MORTY pair
+    let order = make_random_orders(pubkey, &secret, "MORTY".into(), "RICK".into(), 1).remove(0);
+    block_on(insert_or_update_order(&ctx, order.clone()));
+    let mut updated = order.clone();
+    updated.price = BigRational::from_integer(100.into());
+    block_on(insert_or_update_order(&ctx, updated.clone()));
+
+    block_on(ordermatch_ctx.orderbook.lock()).remove_order_trie_update(order.uuid);
+
+    let expected = vec![
+        OrderbookEvent::OrderInserted(order),
+        OrderbookEvent::OrderUpdated(updated.clone()),
+        OrderbookEvent::OrderRemoved(updated),
+    ];
+    let events: Vec<_> = iter::from_fn(|| rick_morty_rx.try_next().ok().flatten()).collect();
+    assert_eq!(events, expected);
+    let events: Vec<_> = iter::from_fn(|| all_rx.try_next().ok().flatten()).collect();
+    assert_eq!(events, expected);
+    assert!(rick_eth_rx.try_next().is_err());
+
+    // the dropped subscriber is removed on the next event
+    drop(all_rx);
+    let order = make_random_orders("pubkey".into(), &secret, "RICK".into(), "ETH".into(), 1).remove(0);
+    block_on(insert_or_update_order(&ctx, order.clone()));
+    assert_eq!(
+        rick_eth_rx.try_next().unwrap(),
+        Some(OrderbookEvent::OrderInserted(order))
+    );
+    assert_eq!(block_on(ordermatch_ctx.orderbook.lock()).events_subscribers.len(), 2);
+}
+
+#[test]
+fn test_orderbook_events_buffer_overflow() {
+    let mut orderbook = Orderbook {
+        max_orders_per_pubkey: None,
+        ..Default::default()
+    };
+    let mut rx = orderbook.subscribe_events(None);
+
+    let (pubkey, secret) = pubkey_and_secret_for_test("passphrase");
+    let orders = make_random_orders(
+        pubkey,
+        &secret,
+        "RICK".into(),
+        "MORTY".into(),
+        ORDERBOOK_EVENTS_BUFFER + 2,
+    );
+    for order in orders {
+        orderbook.insert_or_update_order_update_trie(order);
+    }
+
+    // the subscriber that doesn't read the events is unsubscribed once its buffer is full
+    assert!(orderbook.events_subscribers.is_empty());
+    let buffered = iter::from_fn(|| rx.try_next().ok().flatten()).count();
+    assert!(buffered >= ORDERBOOK_EVENTS_BUFFER);
+    // the end of the stream tells the consumer to resubscribe
+    assert_eq!(rx.try_next().unwrap(), None);
+}
+
+#[test]
+fn test_orderbook_events_rpc() {
+    let (ctx, pubkey, secret) = make_ctx_for_tests();
+
+    let res = block_on(orderbook_events_subscribe_rpc(ctx.clone(), json!({}))).unwrap();
+    let res: Json = json::from_slice(res.body()).unwrap();
+    let subscription_id = res["result"]["subscription_id"].clone();
+
+    let order = make_random_orders(pubkey, &secret, "RICK".into(), "MORTY".into(), 1).remove(0);
+    block_on(insert_or_update_order(&ctx, order.clone()));
+
+    let req = json!({ "subscription_id": subscription_id, "timeout": 1 });
+    let res = block_on(orderbook_events_rpc(ctx.clone(), req.clone())).unwrap();
+    let res: Json = json::from_slice(res.body()).unwrap();
+    let expected = json::to_value(vec![OrderbookEvent::OrderInserted(order)]).unwrap();
+    assert_eq!(res["result"]["events"], expected);
+
+    // no events within the timeout
+    let res = block_on(orderbook_events_rpc(ctx.clone(), req)).unwrap();
+    let res: Json = json::from_slice(res.body()).unwrap();
+    assert_eq!(res["result"]["events"], json!([]));
+
+    let err = block_on(orderbook_events_rpc(ctx.clone(), json!({ "subscription_id": 100 }))).unwrap_err();
+    assert!(err.contains("Subscription 100 is not found"), "{}", err);
+
+    let err = block_on(orderbook_events_subscribe_rpc(ctx, json!({ "base": "RICK" }))).unwrap_err();
+    assert!(err.contains("Both base and rel must be set"), "{}", err);
+}
+
+#[test]
+fn test_canonical_ticker() {
+    let ctx = MmCtxBuilder::default()
+        .with_conf(json!({
+            "coins": [{"coin": "KMD"}, {"coin": "BTC"}, {"coin": "tBTC"}, {"coin": "TBTC"}],
+        }))
+        .into_mm_arc();
+    assert_eq!(canonical_ticker(&ctx, "kmd"), "KMD");
+    assert_eq!(canonical_ticker(&ctx, " btc "), "BTC");
+    // the exact match takes precedence over the case insensitive one
+    assert_eq!(canonical_ticker(&ctx, "tBTC"), "tBTC");
+    assert_eq!(canonical_ticker(&ctx, "TBTC"), "TBTC");
+    assert_eq!(canonical_ticker(&ctx, " unknown"), "unknown");
+
+    let pair = |base: &str, rel: &str| (canonical_ticker(&ctx, base), canonical_ticker(&ctx, rel));
+    let (base, rel) = pair("KMD", "BTC");
+    let (other_base, other_rel) = pair("kmd", "btc ");
+    assert_eq!(alb_ordered_pair(&base, &rel), alb_ordered_pair(&other_base, &other_rel));
+    assert_eq!(
+        orderbook_topic_from_base_rel(&base, &rel),
+        orderbook_topic_from_base_rel(&other_base, &other_rel)
+    );
+}
+
+#[test]
+fn test_process_maker_order_created_invalid_pair() {
+    let (ctx, pubkey, _secret) = make_ctx_for_tests();
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+    for (base, rel) in &[(" RICK", "MORTY"), ("RICK", ""), ("RI:CK", "MORTY")] {
+        let uuid = Uuid::new_v4();
+        let mut created = maker_order_created_for_test(uuid);
+        created.base = (*base).into();
+        created.rel = (*rel).into();
+        assert!(!block_on(process_maker_order_created(
+            ctx.clone(),
+            pubkey.clone(),
+            created
+        )));
+        assert!(block_on(ordermatch_ctx.orderbook.lock())
+            .find_order_by_uuid(&uuid)
+            .is_none());
+    }
+
+    let uuid = Uuid::new_v4();
+    assert!(block_on(process_maker_order_created(
+        ctx.clone(),
+        pubkey,
+        maker_order_created_for_test(uuid)
+    )));
+    assert!(block_on(ordermatch_ctx.orderbook.lock())
+        .find_order_by_uuid(&uuid)
+        .is_some());
+}
+
+#[test]
+fn test_process_pubkey_sync_invalid_pair() {
+    let (pubkey, _secret) = pubkey_and_secret_for_test("passphrase");
+    let alb_pair = alb_ordered_pair("RICK", "MORTY");
+    let valid = orderbook_item_for_test(&pubkey, "RICK", "MORTY");
+    let invalid = orderbook_item_for_test(&pubkey, "RICK", "MOR TY");
+    let (valid_uuid, invalid_uuid) = (valid.uuid, invalid.uuid);
+
+    let mut orderbook = Orderbook::default();
+    let full_trie = vec![(valid_uuid, valid.clone()), (invalid_uuid, invalid.clone())];
+    process_pubkey_full_trie(&mut orderbook, &pubkey, &alb_pair, full_trie);
+    assert!(orderbook.find_order_by_uuid(&valid_uuid).is_some());
+    assert!(orderbook.find_order_by_uuid(&invalid_uuid).is_none());
+
+    let mut orderbook = Orderbook::default();
+    let delta = vec![(valid_uuid, Some(valid)), (invalid_uuid, Some(invalid))]
+        .into_iter()
+        .collect();
+    process_trie_delta(&mut orderbook, &pubkey, &alb_pair, delta);
+    assert!(orderbook.find_order_by_uuid(&valid_uuid).is_some());
+    assert!(orderbook.find_order_by_uuid(&invalid_uuid).is_none());
+}
+
+#[test]
+fn test_maker_order_created_schedules_keep_alive() {
+    let (ctx, _pubkey, _secret) = make_ctx_for_tests();
+    let (cmd_tx, mut cmd_rx) = mpsc::channel(10);
+    // the keep-alive is broadcasted by a spawned future, so P2PContext can't be mocked
+    P2PContext::new(cmd_tx).store_to_mm_arc(&ctx);
+
+    let base_coin = TestCoin::new("RICK").into();
+    let rel_coin = TestCoin::new("MORTY").into();
+    for _ in 0..3 {
+        let mut order = MakerOrderBuilder::new(&base_coin, &rel_coin)
+            .with_max_base_vol(10.into())
+            .with_price(1.into())
+            .build_unchecked();
+        order.conf_settings = Some(OrderConfirmationsSettings::default());
+        block_on(maker_order_created_p2p_notify(ctx.clone(), &order));
+    }
+
+    let mut created_num = 0;
+    let mut keep_alives = Vec::new();
+    block_on(async {
+        // the orders created at once are advertised by a single keep-alive
+        Timer::sleep(MY_KEEP_ALIVE_DEBOUNCE_INTERVAL * 2.).await;
+        while let Ok(Some(cmd)) = cmd_rx.try_next() {
+            let msg = match cmd {
+                AdexBehaviourCmd::PublishMsg { msg, .. } => msg,
+                _ => panic!("Unexpected cmd"),
+            };
+            match decode_signed::<new_protocol::OrdermatchMessage>(&msg).unwrap().0 {
+                new_protocol::OrdermatchMessage::MakerOrderCreated(_) => created_num += 1,
+                new_protocol::OrdermatchMessage::PubkeyKeepAlive(keep_alive) => keep_alives.push(keep_alive),
+                _ => panic!("Unexpected message"),
+            }
+        }
+    });
+    assert_eq!(created_num, 3);
+    assert_eq!(keep_alives.len(), 1);
+    assert!(keep_alives[0].trie_roots.contains_key("MORTY:RICK"));
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+    assert!(!ordermatch_ctx.my_keep_alive_scheduled.load(Ordering::Acquire));
+}
+
+#[test]
+fn test_reserved_price_deviates() {
+    let (pubkey, secret) = pubkey_and_secret_for_test("passphrase");
+    let mut orderbook = Orderbook::default();
+    let reserved = |base_amount: MmNumber, rel_amount: MmNumber| MakerReserved {
+        base: "RICK".into(),
+        rel: "MORTY".into(),
+        base_amount,
+        rel_amount,
+        sender_pubkey: H256Json::default(),
+        dest_pub_key: H256Json::default(),
+        maker_order_uuid: Uuid::new_v4(),
+        taker_order_uuid: Uuid::new_v4(),
+        conf_settings: None,
+    };
+    let max_deviation = MmNumber::from("0.1");
+
+    // there are no asks to compare the reservation with
+    assert!(!orderbook.reserved_price_deviates(&reserved(1.into(), 100.into()), &max_deviation));
+
+    for mut order in make_random_orders(pubkey, &secret, "RICK".into(), "MORTY".into(), 3) {
+        order.price = BigRational::from_integer(3.into());
+        orderbook.insert_or_update_order_update_trie(order);
+    }
+    let mut best = make_random_orders(
+        pubkey_and_secret_for_test("best").0,
+        &secret,
+        "RICK".into(),
+        "MORTY".into(),
+        1,
+    )
+    .remove(0);
+    best.price = BigRational::from_integer(2.into());
+    orderbook.insert_or_update_order_update_trie(best);
+    assert_eq!(orderbook.best_ask_price("RICK", "MORTY"), Some(2.into()));
+    assert_eq!(orderbook.best_ask_price("MORTY", "RICK"), None);
+
+    // 2.2 MORTY per RICK is exactly 10% above the best price
+    assert!(!orderbook.reserved_price_deviates(&reserved(5.into(), 11.into()), &max_deviation));
+    assert!(!orderbook.reserved_price_deviates(&reserved(1.into(), 2.into()), &max_deviation));
+    // 2.4 MORTY per RICK is 20% above the best price
+    assert!(orderbook.reserved_price_deviates(&reserved(5.into(), 12.into()), &max_deviation));
+    assert!(orderbook.reserved_price_deviates(&reserved(1.into(), 3.into()), &max_deviation));
+    assert!(!orderbook.reserved_price_deviates(&reserved(1.into(), 3.into()), &MmNumber::from("0.5")));
+}
+
+#[test]
+fn test_process_msg_maker_pubkeys_allowlist() {
+    let (allowed_pubkey, allowed_secret) = pubkey_and_secret_for_test("allowed");
+    let (denied_pubkey, denied_secret) = pubkey_and_secret_for_test("denied");
+    let ctx = MmCtxBuilder::default()
+        .with_conf(json!({ "maker_pubkeys_allowlist": [allowed_pubkey.to_uppercase()] }))
+        .into_mm_arc();
+    let peer = PeerId::random().to_string();
+    let created_msg = || {
+        new_protocol::OrdermatchMessage::MakerOrderCreated(new_protocol::MakerOrderCreated {
+            uuid: Uuid::new_v4().into(),
+            base: "RICK".into(),
+            rel: "MORTY".into(),
+            price: BigRational::from_integer(1.into()),
+            max_volume: BigRational::from_integer(2.into()),
+            min_volume: BigRational::from_integer(1.into()),
+            conf_settings: OrderConfirmationsSettings::default(),
+            created_at: now_ms() / 1000,
+            timestamp: now_ms() / 1000,
+            pair_trie_root: H64::default(),
+        })
+    };
+
+    let msg = encode_and_sign(&created_msg(), &allowed_secret).unwrap();
+    assert!(block_on(process_msg(ctx.clone(), vec![], peer.clone(), &msg, false)));
+    let msg = encode_and_sign(&created_msg(), &denied_secret).unwrap();
+    assert!(!block_on(process_msg(ctx.clone(), vec![], peer.clone(), &msg, false)));
+
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+    let trie_roots = {
+        let orderbook = block_on(ordermatch_ctx.orderbook.lock());
+        assert_eq!(orderbook.order_set.len(), 1);
+        assert!(orderbook.order_set.values().all(|order| order.pubkey == allowed_pubkey));
+        assert!(!orderbook.pubkeys_state.contains_key(&denied_pubkey));
+        orderbook.pubkeys_state[&allowed_pubkey].trie_roots.clone()
+    };
+
+    let keep_alive = new_protocol::OrdermatchMessage::PubkeyKeepAlive(PubkeyKeepAlive {
+        trie_roots,
+        timestamp: now_ms() / 1000,
+    });
+    let msg = encode_and_sign(&keep_alive, &allowed_secret).unwrap();
+    assert!(block_on(process_msg(ctx.clone(), vec![], peer.clone(), &msg, false)));
+    let msg = encode_and_sign(&keep_alive, &denied_secret).unwrap();
+    assert!(!block_on(process_msg(ctx.clone(), vec![], peer, &msg, false)));
+    assert!(!block_on(ordermatch_ctx.orderbook.lock())
+        .pubkeys_state
+        .contains_key(&denied_pubkey));
+}
+
+#[test]
+fn test_maker_pubkeys_allowlist_misconfigured() {
+    let conf = json!({ "maker_pubkeys_allowlist": "03abcdef" });
+    assert!(validate_ordermatch_conf(&conf).is_err());
+
+    // the context is still created so the node doesn't panic if the config wasn't validated
+    let ctx = MmCtxBuilder::default().with_conf(conf).into_mm_arc();
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+    assert_eq!(ordermatch_ctx.maker_pubkeys_allowlist, None);
+    assert!(ordermatch_ctx.is_maker_pubkey_allowed("03abcdef"));
+
+    let conf = json!({ "maker_pubkeys_allowlist": ["03ABCDEF"] });
+    assert_eq!(
+        maker_pubkeys_allowlist_from_conf(&conf).unwrap(),
+        Some(iter::once("03abcdef".to_owned()).collect())
+    );
+}
+
+#[test]
+fn test_validate_taker_conf_settings() {
+    TestCoin::required_confirmations.mock_safe(|coin| {
+        let confs = if coin.ticker() == "ETH" { 0 } else { 1 };
+        MockResult::Return(confs)
+    });
+    let rick: MmCoinEnum = TestCoin::new("RICK").into();
+    let morty: MmCoinEnum = TestCoin::new("MORTY").into();
+    let eth: MmCoinEnum = TestCoin::new("ETH").into();
+    let conf_settings = |base_confs, rel_confs| OrderConfirmationsSettings {
+        base_confs,
+        base_nota: false,
+        rel_confs,
+        rel_nota: false,
+    };
+
+    validate_taker_conf_settings(&conf_settings(1, 10), &rick, &morty, 10).unwrap();
+    validate_taker_conf_settings(&conf_settings(0, 1), &eth, &morty, 10).unwrap();
+
+    let err = validate_taker_conf_settings(&conf_settings(1, 0), &rick, &morty, 10).unwrap_err();
+    assert!(err.contains("Zero confirmations of MORTY"), "{}", err);
+    let err = validate_taker_conf_settings(&conf_settings(0, 1), &rick, &eth, 10).unwrap_err();
+    assert!(err.contains("Zero confirmations of RICK"), "{}", err);
+
+    let err = validate_taker_conf_settings(&conf_settings(11, 1), &rick, &morty, 10).unwrap_err();
+    assert!(err.contains("11 confirmations of RICK"), "{}", err);
+    let err = validate_taker_conf_settings(&conf_settings(1, u64::MAX), &rick, &eth, 10).unwrap_err();
+    assert!(err.contains("of ETH"), "{}", err);
+}
+
+#[test]
+fn test_resync_orderbook() {
+    let (ctx, my_pubkey, my_secret) = make_ctx_for_tests();
+    let (_, mut cmd_rx) = p2p_context_mock();
+
+    let (stale_pubkey, stale_secret) = pubkey_and_secret_for_test("stale");
+    let (fresh_pubkey, fresh_secret) = pubkey_and_secret_for_test("fresh");
+    let stale_orders = make_random_orders(stale_pubkey, &stale_secret, "RICK".into(), "MORTY".into(), 2);
+    let outdated_orders = make_random_orders(fresh_pubkey.clone(), &fresh_secret, "MORTY".into(), "RICK".into(), 2);
+    let my_orders = make_random_orders(my_pubkey, &my_secret, "RICK".into(), "MORTY".into(), 1);
+    for order in stale_orders
+        .iter()
+        .chain(outdated_orders.iter())
+        .chain(my_orders.iter())
+    {
+        block_on(insert_or_update_order(&ctx, order.clone()));
+    }
+
+    let fresh_orders: Vec<_> =
+        make_random_orders(fresh_pubkey.clone(), &fresh_secret, "RICK".into(), "MORTY".into(), 3)
+            .into_iter()
+            .map(|order| (order.uuid, order))
+            .collect();
+    let response_orders = fresh_orders.clone();
+    spawn(async move {
+        loop {
+            let cmd = cmd_rx.next().await.unwrap();
+            let response_tx = match cmd {
+                AdexBehaviourCmd::Subscribe { .. } => continue,
+                AdexBehaviourCmd::RequestAnyRelay { response_tx, .. } => response_tx,
+                _ => panic!("Unexpected cmd"),
+            };
+            let item = GetOrderbookPubkeyItem {
+                orders: response_orders,
+                last_keep_alive: now_ms() / 1000,
+                last_signed_pubkey_payload: vec![],
+            };
+            let orderbook = GetOrderbookRes {
+                pubkey_orders: iter::once((fresh_pubkey, item)).collect(),
+                orderbook_item_version: ORDERBOOK_ITEM_VERSION,
+            };
+            response_tx
+                .send(Some((PeerId::random(), encode_message(&orderbook).unwrap())))
+                .unwrap();
+            break;
+        }
+    });
+
+    let orders_num = block_on(resync_orderbook(&ctx, "RICK", "MORTY")).unwrap();
+    assert_eq!(orders_num, 4);
+
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+    let orderbook = block_on(ordermatch_ctx.orderbook.lock());
+    let actual: HashSet<_> = orderbook.order_set.keys().copied().collect();
+    let expected: HashSet<_> = fresh_orders
+        .iter()
+        .map(|(uuid, _)| *uuid)
+        .chain(my_orders.iter().map(|order| order.uuid))
+        .collect();
+    assert_eq!(actual, expected);
+    assert!(orderbook
+        .unordered
+        .get(&("MORTY".to_owned(), "RICK".to_owned()))
+        .is_none());
+}
+
+#[test]
+fn test_resync_orderbook_no_relays_responded() {
+    let (ctx, _, _) = make_ctx_for_tests();
+    let (_, mut cmd_rx) = p2p_context_mock();
+
+    let (pubkey, secret) = pubkey_and_secret_for_test("passphrase");
+    let orders = make_random_orders(pubkey, &secret, "RICK".into(), "MORTY".into(), 2);
+    for order in orders.iter() {
+        block_on(insert_or_update_order(&ctx, order.clone()));
+    }
+
+    spawn(async move {
+        loop {
+            match cmd_rx.next().await.unwrap() {
+                AdexBehaviourCmd::Subscribe { .. } => continue,
+                AdexBehaviourCmd::RequestAnyRelay { response_tx, .. } => {
+                    response_tx.send(None).unwrap();
+                    break;
+                },
+                _ => panic!("Unexpected cmd"),
+            }
+        }
+    });
+
+    let err = block_on(resync_orderbook(&ctx, "RICK", "MORTY")).unwrap_err();
+    assert!(err.contains("Only 0 of 1 required relays responded"), "{}", err);
+
+    // the orders are not purged as there is nothing to replace them with
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+    let orderbook = block_on(ordermatch_ctx.orderbook.lock());
+    let actual: HashSet<_> = orderbook.order_set.keys().copied().collect();
+    let expected: HashSet<_> = orders.iter().map(|order| order.uuid).collect();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_taker_orders_pair_filter() {
+    let (ctx, _, _) = make_ctx_for_tests();
+    let (_cmd_tx, _cmd_rx) = p2p_context_mock();
+    save_my_taker_order.mock_safe(|_, _| MockResult::Return(()));
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+    let deny_morty_rick = json!({
+        "pair_filter": {
+            "denylist": [{"base": "MORTY", "rel": "RICK"}],
+        }
+    });
+    *block_on(ordermatch_ctx.pair_filter.lock()) = PairFilter::from_conf(&deny_morty_rick).unwrap();
+
+    let rick: MmCoinEnum = TestCoin::new("RICK").into();
+    let morty: MmCoinEnum = TestCoin::new("MORTY").into();
+
+    // buying RICK for MORTY is the same as selling MORTY for RICK
+    let input: AutoBuyInput = json::from_value(json!({
+        "base": "RICK",
+        "rel": "MORTY",
+        "price": "1",
+        "volume": "1",
+        "method": "buy",
+    }))
+    .unwrap();
+    let err = block_on(lp_auto_buy(&ctx, &rick, &morty, input)).unwrap_err();
+    assert!(
+        err.contains("The pair MORTY/RICK is denied by the pair filter"),
+        "{}",
+        err
+    );
+
+    let taker_order = TakerOrderBuilder::new(&rick, &morty)
+        .with_base_amount(1.into())
+        .with_rel_amount(1.into())
+        .with_action(TakerAction::Buy)
+        .build_unchecked();
+    assert_eq!(taker_order.request.maker_pair(), ("MORTY", "RICK"));
+    let taker_uuid = taker_order.request.uuid;
+    block_on(ordermatch_ctx.my_taker_orders.lock()).insert(taker_uuid, taker_order);
+
+    let maker_uuid = Uuid::new_v4();
+    let maker_pubkey: H256Json = [1; 32].into();
+    let reserved = MakerReserved {
+        base: "RICK".into(),
+        rel: "MORTY".into(),
+        base_amount: 1.into(),
+        rel_amount: 1.into(),
+        sender_pubkey: maker_pubkey.clone(),
+        dest_pub_key: H256Json::default(),
+        maker_order_uuid: maker_uuid,
+        taker_order_uuid: taker_uuid,
+        conf_settings: None,
+    };
+
+    block_on(process_maker_reserved(
+        ctx.clone(),
+        maker_pubkey.clone(),
+        reserved.clone(),
+    ));
+    assert!(block_on(ordermatch_ctx.my_taker_orders.lock())[&taker_uuid]
+        .matches
+        .is_empty());
+
+    *block_on(ordermatch_ctx.pair_filter.lock()) = PairFilter::default();
+    block_on(process_maker_reserved(ctx.clone(), maker_pubkey, reserved));
+    assert!(block_on(ordermatch_ctx.my_taker_orders.lock())[&taker_uuid]
+        .matches
+        .contains_key(&maker_uuid));
+}
+
+#[test]
+fn test_pair_filter_misconfigured() {
+    let conf = json!({
+        "pair_filter": {
+            "denylist": "MORTY/RICK",
+        }
+    });
+    assert!(validate_ordermatch_conf(&conf).is_err());
+
+    // the context is still created so the node doesn't panic if the config wasn't validated
+    let ctx = MmCtxBuilder::default().with_conf(conf).into_mm_arc();
+    let ordermatch_ctx = OrdermatchContext::from_ctx(&ctx).unwrap();
+    assert_eq!(*block_on(ordermatch_ctx.pair_filter.lock()), PairFilter::default());
+}
@@ -54,6 +54,8 @@ const PUBLIC_METHODS: &[Option<&str>] = &[
     Some("metrics"),
     Some("notify"), // Manually checks the peer's public key.
     Some("orderbook"),
+    Some("orderbook_snapshot"),
+    Some("orderbook_snapshot_diff"),
     Some("passphrase"), // Manually checks the "passphrase".
     Some("pricearray"),
     Some("psock"),
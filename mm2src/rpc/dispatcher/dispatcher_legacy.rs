@@ -9,9 +9,12 @@ use serde_json::{self as json, Value as Json};
 use std::net::SocketAddr;
 
 use super::lp_commands::*;
-use crate::mm2::lp_ordermatch::{best_orders_rpc, buy, cancel_all_orders, cancel_order, my_orders, order_status,
-                                orderbook_depth_rpc, orderbook_rpc, orders_history_by_filter, sell, set_price,
-                                update_maker_order};
+use crate::mm2::lp_ordermatch::{best_orders_rpc, buy, cancel_all_orders, cancel_order, matchable_pairs_rpc, my_orders,
+                                order_by_uuid_rpc, order_status, orderbook_depth_rpc, orderbook_events_rpc,
+                                orderbook_events_subscribe_rpc, orderbook_rpc, orderbook_snapshot_diff_rpc,
+                                orderbook_snapshot_rpc, orderbook_stats_rpc, orders_history_by_filter, sell,
+                                set_pair_filter_rpc, set_price, swap_outcomes_stats_rpc, trading_pair_config_rpc,
+                                update_maker_order, update_orderbook_rpc};
 use crate::mm2::lp_swap::{active_swaps_rpc, all_swaps_uuids_by_filter, ban_pubkey_rpc, coins_needed_for_kick_start,
                           import_swaps, list_banned_pubkeys_rpc, max_taker_vol, my_recent_swaps, my_swap_status,
                           recover_funds_of_swap, stats_swap_status, unban_pubkeys_rpc};
@@ -95,6 +98,7 @@ pub fn dispatcher(req: Json, ctx: MmArc) -> DispatcherRes {
         "kmd_rewards_info" => hyres(kmd_rewards_info(ctx)),
         // "inventory" => inventory (ctx, req),
         "list_banned_pubkeys" => hyres(list_banned_pubkeys_rpc(ctx)),
+        "matchable_pairs" => hyres(matchable_pairs_rpc(ctx, req)),
         "max_taker_vol" => hyres(max_taker_vol(ctx, req)),
         "metrics" => metrics(ctx),
         "min_trading_vol" => hyres(min_trading_vol(ctx, req)),
@@ -104,9 +108,15 @@ pub fn dispatcher(req: Json, ctx: MmArc) -> DispatcherRes {
         "my_swap_status" => my_swap_status(ctx, req),
         "my_tx_history" => hyres(my_tx_history(ctx, req)),
         "orders_history_by_filter" => hyres(orders_history_by_filter(ctx, req)),
+        "order_by_uuid" => hyres(order_by_uuid_rpc(ctx, req)),
         "order_status" => hyres(order_status(ctx, req)),
         "orderbook" => hyres(orderbook_rpc(ctx, req)),
         "orderbook_depth" => hyres(orderbook_depth_rpc(ctx, req)),
+        "orderbook_events" => hyres(orderbook_events_rpc(ctx, req)),
+        "orderbook_events_subscribe" => hyres(orderbook_events_subscribe_rpc(ctx, req)),
+        "orderbook_snapshot" => hyres(orderbook_snapshot_rpc(ctx, req)),
+        "orderbook_snapshot_diff" => hyres(orderbook_snapshot_diff_rpc(ctx, req)),
+        "orderbook_stats" => hyres(orderbook_stats_rpc(ctx, req)),
         "sim_panic" => hyres(sim_panic(req)),
         "recover_funds_of_swap" => {
             #[cfg(not(target_arch = "wasm32"))]
@@ -121,14 +131,18 @@ pub fn dispatcher(req: Json, ctx: MmArc) -> DispatcherRes {
         "sell" => hyres(sell(ctx, req)),
         "show_priv_key" => hyres(show_priv_key(ctx, req)),
         "send_raw_transaction" => hyres(send_raw_transaction(ctx, req)),
+        "set_pair_filter" => hyres(set_pair_filter_rpc(ctx, req)),
         "set_required_confirmations" => hyres(set_required_confirmations(ctx, req)),
         "set_requires_notarization" => hyres(set_requires_notarization(ctx, req)),
         "setprice" => hyres(set_price(ctx, req)),
         "stats_swap_status" => stats_swap_status(ctx, req),
         "stop" => stop(ctx),
+        "swap_outcomes_stats" => hyres(swap_outcomes_stats_rpc(ctx, req)),
         "trade_preimage" => hyres(into_legacy::trade_preimage(ctx, req)),
+        "trading_pair_config" => hyres(trading_pair_config_rpc(ctx, req)),
         "unban_pubkeys" => hyres(unban_pubkeys_rpc(ctx, req)),
         "update_maker_order" => hyres(update_maker_order(ctx, req)),
+        "update_orderbook" => hyres(update_orderbook_rpc(ctx, req)),
         "validateaddress" => hyres(validate_address(ctx, req)),
         "version" => version(),
         "withdraw" => hyres(into_legacy::withdraw(ctx, req)),